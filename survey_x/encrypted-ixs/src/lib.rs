@@ -16,55 +16,631 @@ mod circuits {
         input_ctxt.owner.from_arcis(sum)
     }
 
-    // Updated confidential instruction for submitting survey responses with sealing
+    // Structured survey response, one ciphertext per field
+    pub struct ResponseInput {
+        response: u64,           // Raw response value
+        rating: u8,              // Rating (1-10)
+        feedback_length: u16,    // Length of free-text feedback
+        category: u8,            // Category (1-5)
+        timestamp: u64,          // Client-side answer timestamp (Unix)
+    }
+
+    // Fixed-size output structure for survey owner
+    pub struct ProcessedResponse {
+        score: u64,              // Final calculated score
+        category_score: u64,     // Category-specific score
+        time_bonus: u64,         // Time-based bonus
+        total: u64,              // Total score
+    }
+
+    // Loan eligibility result for loan officer (sealing example)
+    pub struct LoanEligibility {
+        is_eligible: bool,       // Whether applicant meets requirements
+        risk_score: u8,          // Risk assessment (1-10)
+        recommended_limit: u64,  // Suggested loan limit
+    }
+
+    // Running per-survey aggregates, held as MXE-owned state on the survey
+    pub struct SurveyAnalytics {
+        total_responses: u64,    // Number of processed responses
+        total_score: u64,        // Sum of ProcessedResponse.total
+        total_rating: u64,       // Sum of ratings
+        eligible_responses: u64, // Responses flagged as eligible
+    }
+
+    #[instruction]
+    pub fn init_survey_analytics(mxe: Mxe) -> Enc<Mxe, SurveyAnalytics> {
+        let analytics = SurveyAnalytics {
+            total_responses: 0u64,
+            total_score: 0u64,
+            total_rating: 0u64,
+            eligible_responses: 0u64,
+        };
+        mxe.from_arcis(analytics)
+    }
+
+    // Confidential instruction for submitting survey responses with sealing.
+    // `submitted_at` is the on-chain clock at queue time, used as the reference
+    // point for the time bonus. The survey's running analytics are updated in
+    // the same pass and handed back to the MXE.
     #[instruction]
     pub fn submit_response(
-        response_ctxt: Enc<Shared, u64>,
-        loan_officer: Shared
-    ) -> (Enc<Shared, u64>, Enc<Shared, u8>) {
-        let v = response_ctxt.to_arcis();
-        
-        // Process the response and create eligibility assessment
-        let eligibility_score = if v > 1000u64 {
-            8u8 // High eligibility
-        } else if v > 500u64 {
-            6u8 // Medium eligibility
-        } else if v > 100u64 {
-            4u8 // Low eligibility
+        input_ctxt: Enc<Shared, ResponseInput>,
+        analytics_ctxt: Enc<Mxe, &SurveyAnalytics>,
+        loan_officer: Shared,
+        submitted_at: u64
+    ) -> (Enc<Shared, ProcessedResponse>, Enc<Shared, LoanEligibility>, Enc<Mxe, SurveyAnalytics>) {
+        let input = input_ctxt.to_arcis();
+        let mut analytics = *(analytics_ctxt.to_arcis());
+
+        // Base calculations (cheap operations)
+        let weighted_response = input.response * 2u64;
+        let rating_bonus = (input.rating as u64) * 10u64;
+        let base_score = weighted_response + rating_bonus;
+
+        // Category bonus calculation (data independent - all paths execute)
+        let category_bonus = if input.category > 3u8 {
+            50u64
+        } else if input.category > 1u8 {
+            25u64
+        } else {
+            10u64
+        };
+
+        // Feedback bonus calculation (data independent)
+        let feedback_bonus = if input.feedback_length > 100u16 {
+            30u64
+        } else if input.feedback_length > 50u16 {
+            20u64
+        } else {
+            10u64
+        };
+
+        // Time-based calculations (data independent)
+        let time_diff = if input.timestamp > submitted_at {
+            input.timestamp - submitted_at
         } else {
-            2u8 // Very low eligibility
+            submitted_at - input.timestamp
         };
-        
+
+        let time_bonus = if time_diff < 86400u64 { // Within 24 hours
+            20u64
+        } else {
+            5u64
+        };
+
+        let total_score = base_score + category_bonus + feedback_bonus + time_bonus;
+
+        let owner_output = ProcessedResponse {
+            score: base_score,
+            category_score: category_bonus,
+            time_bonus: time_bonus,
+            total: total_score,
+        };
+
+        // Loan eligibility assessment without revealing exact values
+        let min_balance_required = 1000u64;
+        let is_eligible = total_score >= min_balance_required;
+
+        let risk_score = if input.rating >= 8u8 && time_diff < 86400u64 {
+            2u8 // Low risk
+        } else if input.rating >= 6u8 && time_diff < 172800u64 {
+            5u8 // Medium risk
+        } else {
+            8u8 // High risk
+        };
+
+        let recommended_limit = if total_score > 200u64 {
+            10000u64
+        } else if total_score > 150u64 {
+            5000u64
+        } else if total_score > 100u64 {
+            2000u64
+        } else {
+            500u64
+        };
+
+        let loan_officer_output = LoanEligibility {
+            is_eligible: is_eligible,
+            risk_score: risk_score,
+            recommended_limit: recommended_limit,
+        };
+
+        // Update running totals (data independent)
+        analytics.total_responses += 1u64;
+        analytics.total_score += total_score;
+        analytics.total_rating += input.rating as u64;
+        analytics.eligible_responses += if is_eligible { 1u64 } else { 0u64 };
+
         // Return results for different recipients (sealing pattern)
-        let owner_result = response_ctxt.owner.from_arcis(v);
-        let loan_officer_result = loan_officer.from_arcis(eligibility_score);
-        
-        (owner_result, loan_officer_result)
+        let owner_result = input_ctxt.owner.from_arcis(owner_output);
+        let loan_officer_result = loan_officer.from_arcis(loan_officer_output);
+        let analytics_result = analytics_ctxt.owner.from_arcis(analytics);
+
+        (owner_result, loan_officer_result, analytics_result)
+    }
+
+    // ProcessedResponse.total at which a response earns the full reward budget
+    const FULL_REWARD_SCORE: u64 = 300;
+
+    // Quality-weighted reward for a processed response. The response's scores
+    // stay encrypted; only the payout, capped by `budget`, is revealed.
+    #[instruction]
+    pub fn compute_reward(processed_ctxt: Enc<Shared, &ProcessedResponse>, budget: u64) -> u64 {
+        let processed = *(processed_ctxt.to_arcis());
+
+        let capped_score = if processed.total > FULL_REWARD_SCORE {
+            FULL_REWARD_SCORE
+        } else {
+            processed.total
+        };
+        let payout = (budget as u128 * capped_score as u128) / (FULL_REWARD_SCORE as u128);
+
+        (payout as u64).reveal()
+    }
+
+    // Quizzes are fixed-size; unused question slots carry zero points
+    const MAX_QUIZ_QUESTIONS: usize = 8;
+
+    // Quiz answer key, held as MXE-owned state on the quiz account
+    pub struct AnswerKey {
+        answers: [u8; MAX_QUIZ_QUESTIONS], // Correct option index per question
+        points: [u8; MAX_QUIZ_QUESTIONS],  // Points awarded per correct answer
+    }
+
+    // A participant's selected option index per question
+    pub struct QuizChoices {
+        choices: [u8; MAX_QUIZ_QUESTIONS],
+    }
+
+    // Re-encrypts the creator's answer key to the MXE. After this, nobody
+    // (including the creator) holds a key that decrypts the stored answers.
+    #[instruction]
+    pub fn upload_answer_key(key_ctxt: Enc<Shared, AnswerKey>, mxe: Mxe) -> Enc<Mxe, AnswerKey> {
+        let key = key_ctxt.to_arcis();
+        mxe.from_arcis(key)
+    }
+
+    // Grades a quiz attempt against the encrypted answer key. Every question is
+    // compared, so the circuit leaks nothing about which answers were correct.
+    // Only whether the score reaches `minimum_score` is revealed.
+    #[instruction]
+    pub fn grade_quiz(
+        choices_ctxt: Enc<Shared, QuizChoices>,
+        key_ctxt: Enc<Mxe, &AnswerKey>,
+        minimum_score: u64
+    ) -> (Enc<Shared, u64>, bool) {
+        let choices = choices_ctxt.to_arcis();
+        let key = *(key_ctxt.to_arcis());
+
+        let mut score = 0u64;
+        for i in 0..MAX_QUIZ_QUESTIONS {
+            score += if choices.choices[i] == key.answers[i] {
+                key.points[i] as u64
+            } else {
+                0u64
+            };
+        }
+
+        let passed = score >= minimum_score;
+
+        (choices_ctxt.owner.from_arcis(score), passed.reveal())
+    }
+
+    // Returned in place of every aggregate field while fewer than the survey's
+    // k-anonymity minimum of responses have been recorded
+    const SUPPRESSED: u64 = u64::MAX;
+
+    // Noise is two-sided geometric (discrete Laplace) with alpha = exp(-epsilon /
+    // sensitivity). A geometric sample's binary digits are independent
+    // Bernoulli(alpha^(2^k) / (1 + alpha^(2^k))), so it is drawn digit by digit
+    // from per-digit thresholds the program computes in plaintext.
+    const NOISE_DIGITS: usize = 16;
+    // Random bits per Bernoulli trial; thresholds are scaled to 2^NOISE_BITS
+    const NOISE_BITS: usize = 16;
+
+    // Succeeds with probability threshold / 2^NOISE_BITS, using MPC randomness
+    fn bernoulli(threshold: u64) -> bool {
+        let mut r = 0u64;
+        for i in 0..NOISE_BITS {
+            r += if ArcisRNG::bool() { 1u64 << i } else { 0u64 };
+        }
+        r < threshold
+    }
+
+    fn geometric(thresholds: [u64; NOISE_DIGITS]) -> u64 {
+        let mut g = 0u64;
+        for k in 0..NOISE_DIGITS {
+            g += if bernoulli(thresholds[k]) { 1u64 << k } else { 0u64 };
+        }
+        g
+    }
+
+    // Adds discrete Laplace noise, clamping the result at zero
+    fn add_noise(value: u64, thresholds: [u64; NOISE_DIGITS]) -> u64 {
+        let up = geometric(thresholds);
+        let down = geometric(thresholds);
+        let raised = value + up;
+        if raised > down { raised - down } else { 0u64 }
+    }
+
+    // Options per multiple-choice question; each gets one histogram bucket
+    const MAX_CHOICE_OPTIONS: usize = 8;
+
+    #[instruction]
+    pub fn init_choice_tally(mxe: Mxe) -> Enc<Mxe, [u64; MAX_CHOICE_OPTIONS]> {
+        mxe.from_arcis([0u64; MAX_CHOICE_OPTIONS])
+    }
+
+    // Adds one respondent's option index to the question's histogram. Every
+    // bucket is rewritten, so the update pattern reveals nothing about the
+    // choice. Indices at or above the question's `option_count` are not
+    // counted, and only whether the choice was counted is revealed.
+    #[instruction]
+    pub fn record_choice(
+        choice_ctxt: Enc<Shared, u8>,
+        tally_ctxt: Enc<Mxe, &[u64; MAX_CHOICE_OPTIONS]>,
+        option_count: u64
+    ) -> (Enc<Mxe, [u64; MAX_CHOICE_OPTIONS]>, bool) {
+        let choice = choice_ctxt.to_arcis();
+        let mut tally = *(tally_ctxt.to_arcis());
+        let accepted = (choice as u64) < option_count;
+
+        for i in 0..MAX_CHOICE_OPTIONS {
+            tally[i] += if accepted && choice == i as u8 { 1u64 } else { 0u64 };
+        }
+
+        (tally_ctxt.owner.from_arcis(tally), accepted.reveal())
+    }
+
+    // Re-encrypts a noised copy of a question's histogram to the requesting
+    // owner or analyst. One respondent moves one bucket by one, so every bucket
+    // gets sensitivity-1 noise. Below `min_responses` answers every bucket is
+    // replaced with SUPPRESSED.
+    #[instruction]
+    pub fn reveal_choice_tally(
+        tally_ctxt: Enc<Mxe, &[u64; MAX_CHOICE_OPTIONS]>,
+        recipient: Shared,
+        min_responses: u64,
+        noise_thresholds: [u64; NOISE_DIGITS]
+    ) -> Enc<Shared, [u64; MAX_CHOICE_OPTIONS]> {
+        let tally = *(tally_ctxt.to_arcis());
+
+        let mut total = 0u64;
+        for i in 0..MAX_CHOICE_OPTIONS {
+            total += tally[i];
+        }
+        let suppressed = total < min_responses;
+
+        let mut released = [0u64; MAX_CHOICE_OPTIONS];
+        for i in 0..MAX_CHOICE_OPTIONS {
+            let noisy = add_noise(tally[i], noise_thresholds);
+            released[i] = if suppressed { SUPPRESSED } else { noisy };
+        }
+        recipient.from_arcis(released)
+    }
+
+    // Ratings above this are clamped so one bad input cannot skew the statistics
+    const MAX_RATING: u8 = 10;
+
+    // Fixed-point scale of the mean and variance in RatingSummary
+    const RATING_SCALE: u128 = 1000;
+
+    // Running accumulators for one rating question
+    pub struct RatingStats {
+        count: u64,              // Ratings recorded
+        sum: u64,                // Sum of ratings
+        sum_sq: u64,             // Sum of squared ratings
+    }
+
+    // Finalized statistics, mean and variance scaled by RATING_SCALE
+    pub struct RatingSummary {
+        count: u64,
+        mean: u64,
+        variance: u64,           // Population variance
     }
 
-    // Updated confidential instruction for creating surveys with sealing
+    #[instruction]
+    pub fn init_rating_stats(mxe: Mxe) -> Enc<Mxe, RatingStats> {
+        let stats = RatingStats {
+            count: 0u64,
+            sum: 0u64,
+            sum_sq: 0u64,
+        };
+        mxe.from_arcis(stats)
+    }
+
+    #[instruction]
+    pub fn record_rating(
+        rating_ctxt: Enc<Shared, u8>,
+        stats_ctxt: Enc<Mxe, &RatingStats>
+    ) -> Enc<Mxe, RatingStats> {
+        let input = rating_ctxt.to_arcis();
+        let mut stats = *(stats_ctxt.to_arcis());
+
+        let clamped = if input > MAX_RATING { MAX_RATING } else { input };
+        let rating = clamped as u64;
+        stats.count += 1u64;
+        stats.sum += rating;
+        stats.sum_sq += rating * rating;
+
+        stats_ctxt.owner.from_arcis(stats)
+    }
+
+    // Computes mean and variance from noised accumulators and seals them to the
+    // requesting owner or analyst. Count, sum and sum of squares each get noise
+    // for their own sensitivity (1, MAX_RATING and MAX_RATING^2), so the derived
+    // statistics are differentially private too. Below `min_responses` ratings
+    // only SUPPRESSED is returned.
+    #[instruction]
+    pub fn finalize_rating_stats(
+        stats_ctxt: Enc<Mxe, &RatingStats>,
+        recipient: Shared,
+        min_responses: u64,
+        count_thresholds: [u64; NOISE_DIGITS],
+        sum_thresholds: [u64; NOISE_DIGITS],
+        sum_sq_thresholds: [u64; NOISE_DIGITS]
+    ) -> Enc<Shared, RatingSummary> {
+        let stats = *(stats_ctxt.to_arcis());
+
+        let count = add_noise(stats.count, count_thresholds);
+        let noisy_sum = add_noise(stats.sum, sum_thresholds);
+        let noisy_sum_sq = add_noise(stats.sum_sq, sum_sq_thresholds);
+
+        // Guard the division without branching on whether any ratings exist
+        let n = if count == 0u64 { 1u128 } else { count as u128 };
+        let sum = noisy_sum as u128;
+        let sum_sq = noisy_sum_sq as u128;
+
+        // Noise can push the estimate below zero; clamp instead of underflowing
+        let spread = if n * sum_sq > sum * sum { n * sum_sq - sum * sum } else { 0u128 };
+        let mean = sum * RATING_SCALE / n;
+        let variance = spread * RATING_SCALE / (n * n);
+
+        let suppressed = stats.count < min_responses;
+        let summary = RatingSummary {
+            count: if suppressed { SUPPRESSED } else { count },
+            mean: if suppressed { SUPPRESSED } else { mean as u64 },
+            variance: if suppressed { SUPPRESSED } else { variance as u64 },
+        };
+        recipient.from_arcis(summary)
+    }
+
+    // Contingency table dimensions: the most options the row and column
+    // questions may offer. Must match CROSS_TAB_OPTIONS in the program.
+    const CROSS_TAB_ROWS: usize = 4;
+    const CROSS_TAB_COLS: usize = 4;
+
+    // A respondent's option indices for the table's row and column questions
+    pub struct AnswerPair {
+        row: u8,
+        col: u8,
+    }
+
+    #[instruction]
+    pub fn init_cross_tab(mxe: Mxe) -> Enc<Mxe, [[u64; CROSS_TAB_COLS]; CROSS_TAB_ROWS]> {
+        mxe.from_arcis([[0u64; CROSS_TAB_COLS]; CROSS_TAB_ROWS])
+    }
+
+    // Adds one respondent's answer pair to the table. Every cell is rewritten,
+    // so nothing about the pair leaks. The pair is only counted if both indices
+    // are within their question's options, and only whether it was counted is
+    // revealed.
+    #[instruction]
+    pub fn record_cross_tab(
+        pair_ctxt: Enc<Shared, AnswerPair>,
+        table_ctxt: Enc<Mxe, &[[u64; CROSS_TAB_COLS]; CROSS_TAB_ROWS]>,
+        row_options: u64,
+        col_options: u64
+    ) -> (Enc<Mxe, [[u64; CROSS_TAB_COLS]; CROSS_TAB_ROWS]>, bool) {
+        let pair = pair_ctxt.to_arcis();
+        let mut table = *(table_ctxt.to_arcis());
+        let accepted = (pair.row as u64) < row_options && (pair.col as u64) < col_options;
+
+        for r in 0..CROSS_TAB_ROWS {
+            for c in 0..CROSS_TAB_COLS {
+                let hit = accepted && pair.row == r as u8 && pair.col == c as u8;
+                table[r][c] += if hit { 1u64 } else { 0u64 };
+            }
+        }
+
+        (table_ctxt.owner.from_arcis(table), accepted.reveal())
+    }
+
+    // Re-encrypts a noised copy of the table to the requesting owner or analyst.
+    // Cells whose noised count is below `min_cell_size` are replaced with
+    // SUPPRESSED, so the released pattern depends on the true count only through
+    // the noise. The whole table is suppressed below `min_responses`.
+    #[instruction]
+    pub fn reveal_cross_tab(
+        table_ctxt: Enc<Mxe, &[[u64; CROSS_TAB_COLS]; CROSS_TAB_ROWS]>,
+        recipient: Shared,
+        min_responses: u64,
+        min_cell_size: u64,
+        noise_thresholds: [u64; NOISE_DIGITS]
+    ) -> Enc<Shared, [[u64; CROSS_TAB_COLS]; CROSS_TAB_ROWS]> {
+        let table = *(table_ctxt.to_arcis());
+
+        let mut total = 0u64;
+        for r in 0..CROSS_TAB_ROWS {
+            for c in 0..CROSS_TAB_COLS {
+                total += table[r][c];
+            }
+        }
+        let table_suppressed = total < min_responses;
+
+        let mut released = [[0u64; CROSS_TAB_COLS]; CROSS_TAB_ROWS];
+        for r in 0..CROSS_TAB_ROWS {
+            for c in 0..CROSS_TAB_COLS {
+                let noisy = add_noise(table[r][c], noise_thresholds);
+                let suppressed = table_suppressed || noisy < min_cell_size;
+                released[r][c] = if suppressed { SUPPRESSED } else { noisy };
+            }
+        }
+        recipient.from_arcis(released)
+    }
+
+    // Structured survey metadata, one ciphertext per field
+    pub struct SurveyData {
+        title_length: u8,        // Supported: u8
+        description_length: u8,   // Supported: u8
+        question_count: u8,       // Supported: u8
+        complexity_score: u8,     // Supported: u8 (1-10)
+        target_audience: u8,      // Supported: u8 (1-5)
+        estimated_duration: u16,  // Supported: u16 (minutes)
+    }
+
+    // Fixed-size output structure for survey creator
+    pub struct SurveyMetrics {
+        base_score: u64,          // Base calculation score
+        complexity_bonus: u64,    // Complexity-based bonus
+        audience_bonus: u64,      // Audience-targeting bonus
+        duration_bonus: u64,      // Duration-based bonus
+        total_score: u64,         // Total calculated score
+        quality_tier: u8,         // Quality tier (1-5)
+    }
+
+    // Survey analysis result for survey analyst (sealing example)
+    pub struct SurveyAnalysis {
+        market_fit_score: u8,     // Market fit assessment (1-10)
+        target_demographic: u8,   // Primary demographic (1-5)
+        estimated_response_rate: u8, // Expected response rate (1-100)
+        competitive_advantage: u8,   // Competitive positioning (1-10)
+    }
+
+    // Confidential instruction for creating surveys with sealing
     #[instruction]
     pub fn create_survey(
-        survey_metadata_ctxt: Enc<Shared, u64>,
+        input_ctxt: Enc<Shared, SurveyData>,
         survey_analyst: Shared
-    ) -> (Enc<Shared, u64>, Enc<Shared, u8>) {
-        let metadata = survey_metadata_ctxt.to_arcis();
-        
-        // Analyze survey metadata and create insights
-        let quality_score = if metadata > 1000u64 {
-            9u8 // High quality
-        } else if metadata > 500u64 {
-            7u8 // Good quality
-        } else if metadata > 100u64 {
-            5u8 // Medium quality
+    ) -> (Enc<Shared, SurveyMetrics>, Enc<Shared, SurveyAnalysis>) {
+        let input = input_ctxt.to_arcis();
+
+        // Base calculations (cheap operations - additions are fastest)
+        let title_weight = (input.title_length as u64) * 2u64;
+        let desc_weight = (input.description_length as u64) * 3u64;
+        let question_weight = (input.question_count as u64) * 10u64;
+        let base_score = title_weight + desc_weight + question_weight;
+
+        // Complexity bonus calculation (data independent - all paths execute)
+        let complexity_bonus = if input.complexity_score > 7u8 {
+            50u64
+        } else if input.complexity_score > 4u8 {
+            25u64
         } else {
-            3u8 // Low quality
+            10u64
         };
-        
-        // Return results for different recipients (sealing pattern)
-        let creator_result = survey_metadata_ctxt.owner.from_arcis(metadata);
-        let analyst_result = survey_analyst.from_arcis(quality_score);
-        
+
+        // Audience targeting bonus (data independent)
+        let audience_bonus = if input.target_audience > 3u8 {
+            40u64
+        } else if input.target_audience > 1u8 {
+            20u64
+        } else {
+            5u64
+        };
+
+        // Duration-based bonus (data independent)
+        let duration_bonus = if input.estimated_duration > 30u16 {
+            30u64
+        } else if input.estimated_duration > 15u16 {
+            20u64
+        } else {
+            10u64
+        };
+
+        // Total score calculation (cheap addition operations)
+        let total_score = base_score + complexity_bonus + audience_bonus + duration_bonus;
+
+        // Quality tier calculation (data independent)
+        let quality_tier = if total_score > 200u64 {
+            5u8 // Premium
+        } else if total_score > 150u64 {
+            4u8 // High
+        } else if total_score > 100u64 {
+            3u8 // Medium
+        } else if total_score > 50u64 {
+            2u8 // Basic
+        } else {
+            1u8 // Simple
+        };
+
+        // Create output struct for survey creator (all data encrypted together)
+        let creator_output = SurveyMetrics {
+            base_score: base_score,
+            complexity_bonus: complexity_bonus,
+            audience_bonus: audience_bonus,
+            duration_bonus: duration_bonus,
+            total_score: total_score,
+            quality_tier: quality_tier,
+        };
+
+        // Survey analysis for analyst (sealing example)
+        // Calculate market fit without revealing exact survey details
+        let market_fit_score = if input.complexity_score >= 7u8 && input.target_audience >= 4u8 {
+            9u8 // High market fit
+        } else if input.complexity_score >= 5u8 && input.target_audience >= 3u8 {
+            7u8 // Good market fit
+        } else if input.complexity_score >= 3u8 && input.target_audience >= 2u8 {
+            5u8 // Moderate market fit
+        } else {
+            3u8 // Low market fit
+        };
+
+        // Determine target demographic based on complexity and audience
+        let target_demographic = if input.complexity_score >= 8u8 {
+            5u8 // Expert/Professional
+        } else if input.complexity_score >= 6u8 {
+            4u8 // Advanced
+        } else if input.complexity_score >= 4u8 {
+            3u8 // Intermediate
+        } else if input.complexity_score >= 2u8 {
+            2u8 // Beginner
+        } else {
+            1u8 // General
+        };
+
+        // Estimate response rate based on survey characteristics
+        let estimated_response_rate = if input.estimated_duration <= 5u16 && input.question_count <= 5u8 {
+            85u8 // High response rate
+        } else if input.estimated_duration <= 15u16 && input.question_count <= 10u8 {
+            70u8 // Good response rate
+        } else if input.estimated_duration <= 30u16 && input.question_count <= 20u8 {
+            55u8 // Moderate response rate
+        } else {
+            40u8 // Lower response rate
+        };
+
+        // Calculate competitive advantage score
+        let competitive_advantage = if input.complexity_score >= 8u8 && input.target_audience >= 4u8 && input.estimated_duration <= 20u16 {
+            9u8 // High competitive advantage
+        } else if input.complexity_score >= 6u8 && input.target_audience >= 3u8 && input.estimated_duration <= 30u16 {
+            7u8 // Good competitive advantage
+        } else if input.complexity_score >= 4u8 && input.target_audience >= 2u8 {
+            5u8 // Moderate competitive advantage
+        } else {
+            3u8 // Low competitive advantage
+        };
+
+        // Create survey analysis result for analyst
+        let analyst_output = SurveyAnalysis {
+            market_fit_score: market_fit_score,
+            target_demographic: target_demographic,
+            estimated_response_rate: estimated_response_rate,
+            competitive_advantage: competitive_advantage,
+        };
+
+        // Return encrypted results using proper sealing pattern:
+        // 1. Survey creator gets full metrics (Enc<Shared, SurveyMetrics>)
+        // 2. Survey analyst gets only analysis insights (Enc<Shared, SurveyAnalysis>)
+        // This demonstrates information flow control through re-encryption
+
+        // Group all data for survey creator in one struct to minimize from_arcis calls
+        let creator_result = input_ctxt.owner.from_arcis(creator_output);
+
+        // Re-encrypt survey analysis specifically for survey analyst
+        let analyst_result = survey_analyst.from_arcis(analyst_output);
+
         (creator_result, analyst_result)
     }
 }
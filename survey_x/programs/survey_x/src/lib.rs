@@ -1,21 +1,86 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 use arcium_anchor::prelude::*;
 use std::mem::size_of;
 
 const COMP_DEF_OFFSET_ADD_TOGETHER: u32 = comp_def_offset("add_together");
 const COMP_DEF_OFFSET_SUBMIT_RESPONSE: u32 = comp_def_offset("submit_response");
 const COMP_DEF_OFFSET_CREATE_SURVEY: u32 = comp_def_offset("create_survey");
+const COMP_DEF_OFFSET_INIT_SURVEY_ANALYTICS: u32 = comp_def_offset("init_survey_analytics");
+const COMP_DEF_OFFSET_COMPUTE_REWARD: u32 = comp_def_offset("compute_reward");
+const COMP_DEF_OFFSET_UPLOAD_ANSWER_KEY: u32 = comp_def_offset("upload_answer_key");
+const COMP_DEF_OFFSET_GRADE_QUIZ: u32 = comp_def_offset("grade_quiz");
+const COMP_DEF_OFFSET_INIT_CHOICE_TALLY: u32 = comp_def_offset("init_choice_tally");
+const COMP_DEF_OFFSET_RECORD_CHOICE: u32 = comp_def_offset("record_choice");
+const COMP_DEF_OFFSET_REVEAL_CHOICE_TALLY: u32 = comp_def_offset("reveal_choice_tally");
+const COMP_DEF_OFFSET_INIT_RATING_STATS: u32 = comp_def_offset("init_rating_stats");
+const COMP_DEF_OFFSET_RECORD_RATING: u32 = comp_def_offset("record_rating");
+const COMP_DEF_OFFSET_FINALIZE_RATING_STATS: u32 = comp_def_offset("finalize_rating_stats");
+const COMP_DEF_OFFSET_INIT_CROSS_TAB: u32 = comp_def_offset("init_cross_tab");
+const COMP_DEF_OFFSET_RECORD_CROSS_TAB: u32 = comp_def_offset("record_cross_tab");
+const COMP_DEF_OFFSET_REVEAL_CROSS_TAB: u32 = comp_def_offset("reveal_cross_tab");
+
+// Byte offset of Response.processed: it directly follows the discriminator.
+const RESPONSE_PROCESSED_OFFSET: u32 = 8;
+
+// Co-owners and delegates a survey can hold besides its creator
+const MAX_SURVEY_MEMBERS: usize = 8;
+
+// Must match MAX_QUIZ_QUESTIONS in encrypted-ixs
+const MAX_QUIZ_QUESTIONS: usize = 8;
+
+// Byte offset of Quiz.answer_key: it directly follows the discriminator.
+const QUIZ_ANSWER_KEY_OFFSET: u32 = 8;
+
+// Must match MAX_CHOICE_OPTIONS in encrypted-ixs
+const MAX_CHOICE_OPTIONS: usize = 8;
+
+// Questions per survey that can be tallied or rated: one bit each in the u32
+// `recorded_choices` and `recorded_ratings` masks on Response
+const RECORD_MASK_BITS: u8 = u32::BITS as u8;
+
+// Options a cross-tabulated question may offer. Must match CROSS_TAB_ROWS and
+// CROSS_TAB_COLS in encrypted-ixs.
+const CROSS_TAB_OPTIONS: u8 = 4;
+const CROSS_TAB_CELLS: usize = CROSS_TAB_OPTIONS as usize * CROSS_TAB_OPTIONS as usize;
+
+// Must match NOISE_DIGITS and NOISE_BITS in encrypted-ixs
+const NOISE_DIGITS: usize = 16;
+const NOISE_BITS: u32 = 16;
+
+// Must match MAX_RATING in encrypted-ixs; bounds the sensitivity of rating sums
+const MAX_RATING: u64 = 10;
+
+// Pending computations older than this may be closed; their callback is assumed lost.
+const STALE_COMPUTATION_SECS: i64 = 24 * 60 * 60;
 
 // Computation result storage data structures
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum ComputationType {
     AddTogether,
     SubmitResponse,
     CreateSurvey,
     ProcessSurveyData,
+    InitSurveyAnalytics,
+    ComputeReward,
+    UploadAnswerKey,
+    GradeQuiz,
+    InitChoiceTally,
+    RecordChoice,
+    RevealChoiceTally,
+    InitRatingStats,
+    RecordRating,
+    FinalizeRatingStats,
+    InitCrossTab,
+    RecordCrossTab,
+    RevealCrossTab,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum ComputationStatus {
     Pending,
     Processing,
@@ -23,9 +88,15 @@ pub enum ComputationStatus {
     Failed,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FailureReason {
+    Aborted, // The MPC cluster returned no valid output
+}
+
 #[account]
 pub struct ComputationResult {
-    pub computation_id: [u8; 32],      // Unique computation identifier
+    pub computation_id: [u8; 32],      // Arcium computation account address
+    pub computation_offset: u64,       // Offset the computation was queued with (PDA seed)
     pub computation_type: ComputationType,
     pub status: ComputationStatus,
     pub result_data: [u8; 32],         // Encrypted result data
@@ -35,6 +106,681 @@ pub struct ComputationResult {
     pub creator: Pubkey,               // Who initiated the computation
     pub survey_id: Option<[u8; 32]>,   // Associated survey (if applicable)
     pub response_id: Option<[u8; 32]>, // Associated response (if applicable)
+    pub failure_reason: Option<FailureReason>, // Set when status is Failed
+    pub bump: u8,
+}
+
+impl ComputationResult {
+    // Records a freshly queued computation. The account is keyed by the
+    // computation offset, so clients can derive it from the offset they queued with.
+    pub fn record_queued(
+        &mut self,
+        computation_offset: u64,
+        computation_account: Pubkey,
+        computation_type: ComputationType,
+        creator: Pubkey,
+        bump: u8,
+    ) -> Result<()> {
+        self.computation_id = computation_account.to_bytes();
+        self.computation_offset = computation_offset;
+        self.computation_type = computation_type;
+        self.status = ComputationStatus::Pending;
+        self.result_data = [0u8; 32];
+        self.nonce = [0u8; 16];
+        self.created_at = Clock::get()?.unix_timestamp;
+        self.completed_at = 0;
+        self.creator = creator;
+        self.survey_id = None;
+        self.response_id = None;
+        self.failure_reason = None;
+        self.bump = bump;
+        Ok(())
+    }
+
+    // Commits to a finished result without revealing it. The ciphertext stays
+    // sealed to its recipient; the hash lets a third party check what was stored.
+    pub fn receipt_hash(&self) -> [u8; 32] {
+        hashv(&[
+            &self.computation_id,
+            &self.result_data,
+            &self.nonce,
+            &self.completed_at.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+
+    // Marks the computation as completed. `result_data` is one ciphertext of
+    // the output for clients that only read the record; None leaves it empty.
+    pub fn record_success(&mut self, result_data: Option<[u8; 32]>, nonce: u128) -> Result<()> {
+        self.status = ComputationStatus::Completed;
+        if let Some(result_data) = result_data {
+            self.result_data = result_data;
+        }
+        self.nonce = nonce.to_le_bytes();
+        self.completed_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    // Marks the computation as failed instead of aborting the callback, so the
+    // record survives and clients can tell a failed run from one still in flight.
+    pub fn record_failure(&mut self, reason: FailureReason) -> Result<()> {
+        self.status = ComputationStatus::Failed;
+        self.failure_reason = Some(reason);
+        self.completed_at = Clock::get()?.unix_timestamp;
+
+        emit!(ComputationFailedEvent {
+            computation_offset: self.computation_offset,
+            computation_type: self.computation_type,
+            reason,
+        });
+        Ok(())
+    }
+}
+
+// Survey lifecycle data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SurveyStatus {
+    Draft,
+    Open,
+    Paused,
+    Closed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseMode {
+    Single,                              // One response per wallet
+    Multiple { per_wallet_limit: u16 },  // Up to `per_wallet_limit` responses per wallet
+}
+
+impl ResponseMode {
+    pub fn per_wallet_limit(&self) -> u16 {
+        match self {
+            ResponseMode::Single => 1,
+            ResponseMode::Multiple { per_wallet_limit } => *per_wallet_limit,
+        }
+    }
+}
+
+// On-chain eligibility requirement checked against the respondent's accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ParticipationGate {
+    TokenBalance { mint: Pubkey, min_amount: u64 }, // Hold at least `min_amount` of `mint`
+    NftCollection { collection: Pubkey },           // Hold an NFT from a verified collection
+    QuizWhitelist { quiz: Pubkey },                 // Hold a valid whitelist from passing `quiz`
+}
+
+impl ParticipationGate {
+    pub fn check(
+        &self,
+        respondent: &Pubkey,
+        token_account: Option<&TokenAccount>,
+        metadata: Option<&MetadataAccount>,
+        whitelist: Option<&Whitelist>,
+        now: i64,
+    ) -> Result<()> {
+        match self {
+            ParticipationGate::TokenBalance { mint, min_amount } => {
+                let token_account = Self::respondent_token_account(token_account, respondent)?;
+                require_keys_eq!(token_account.mint, *mint, ErrorCode::ParticipationGateNotMet);
+                require!(
+                    token_account.amount >= *min_amount,
+                    ErrorCode::ParticipationGateNotMet
+                );
+            }
+            ParticipationGate::NftCollection { collection } => {
+                let token_account = Self::respondent_token_account(token_account, respondent)?;
+                let metadata = metadata.ok_or(ErrorCode::MissingGateAccount)?;
+                require_keys_eq!(metadata.mint, token_account.mint, ErrorCode::ParticipationGateNotMet);
+                require!(token_account.amount == 1, ErrorCode::ParticipationGateNotMet);
+                let verified = metadata
+                    .collection
+                    .as_ref()
+                    .is_some_and(|c| c.verified && c.key == *collection);
+                require!(verified, ErrorCode::ParticipationGateNotMet);
+            }
+            ParticipationGate::QuizWhitelist { quiz } => {
+                let whitelist = whitelist.ok_or(ErrorCode::MissingGateAccount)?;
+                require_keys_eq!(whitelist.quiz, *quiz, ErrorCode::ParticipationGateNotMet);
+                require_keys_eq!(whitelist.wallet, *respondent, ErrorCode::ParticipationGateNotMet);
+                require!(whitelist.is_valid(now), ErrorCode::ParticipationGateNotMet);
+            }
+        }
+        Ok(())
+    }
+
+    // Token-based gates need a token account held by the respondent
+    fn respondent_token_account<'a>(
+        token_account: Option<&'a TokenAccount>,
+        respondent: &Pubkey,
+    ) -> Result<&'a TokenAccount> {
+        let token_account = token_account.ok_or(ErrorCode::MissingGateAccount)?;
+        require_keys_eq!(token_account.owner, *respondent, ErrorCode::ParticipationGateNotMet);
+        Ok(token_account)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RewardMode {
+    Fixed,           // Every successful response earns reward_per_response
+    QualityWeighted, // Payout computed in MPC, capped at reward_per_response
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SurveyRole {
+    Owner,   // Full control, same as the creator
+    Editor,  // May pause, resume and extend the survey
+    Analyst, // Receives sealed aggregate outputs
+    Auditor, // Read-only; may request sealed result receipts
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SurveyMember {
+    pub key: Pubkey,
+    pub role: SurveyRole,
+    pub encryption_key: [u8; 32],      // x25519 key outputs for this member are sealed to
+}
+
+impl SurveyMember {
+    pub const EMPTY: SurveyMember = SurveyMember {
+        key: Pubkey::new_from_array([0u8; 32]),
+        role: SurveyRole::Auditor,
+        encryption_key: [0u8; 32],
+    };
+}
+
+// Creator-configured participation rules, fixed at creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SurveySettings {
+    pub starts_at: Option<i64>,        // Earliest accepted submission (Unix)
+    pub ends_at: Option<i64>,          // Submissions at or after this time are rejected
+    pub max_responses: Option<u64>,    // Cap on queued responses
+    pub response_mode: ResponseMode,   // How many times one wallet may respond
+    pub gate: Option<ParticipationGate>, // Token or NFT holding required to respond
+    pub min_reveal_responses: u64,     // k-anonymity floor for aggregate reveals (checked in MPC)
+    pub privacy_budget: u64,           // Total epsilon, in thousandths, all releases may spend
+}
+
+impl SurveySettings {
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(starts_at), Some(ends_at)) = (self.starts_at, self.ends_at) {
+            require!(ends_at > starts_at, ErrorCode::InvalidSurveyWindow);
+        }
+        require!(self.max_responses != Some(0), ErrorCode::InvalidSurveyWindow);
+        require!(
+            self.response_mode.per_wallet_limit() > 0,
+            ErrorCode::InvalidResponseLimit
+        );
+        require!(
+            self.min_reveal_responses > 0,
+            ErrorCode::InvalidRevealThreshold
+        );
+        require!(self.privacy_budget > 0, ErrorCode::InvalidPrivacyBudget);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Survey {
+    pub creator: Pubkey,               // Survey owner
+    pub survey_id: u64,                // Creator-chosen identifier (PDA seed)
+    pub schema_hash: [u8; 32],         // Hash of the off-chain question schema
+    pub metrics: [[u8; 32]; 6],        // SurveyMetrics sealed to the creator
+    pub metrics_nonce: [u8; 16],       // Nonce of the sealed SurveyMetrics
+    pub analyst_key: [u8; 32],         // x25519 key the analysis output is sealed to
+    pub analysis: [[u8; 32]; 4],       // SurveyAnalysis sealed to the analyst
+    pub analysis_nonce: [u8; 16],      // Nonce of the sealed SurveyAnalysis
+    pub settings: SurveySettings,      // Response window and cap
+    pub allowlist_root: Option<[u8; 32]>, // Merkle root of invited respondents, if private
+    pub status: SurveyStatus,
+    pub created_at: i64,               // Creation timestamp
+    pub opened_at: i64,                // Last time the survey was opened or resumed
+    pub closed_at: i64,                // Close timestamp (0 while not closed)
+    pub response_count: u64,           // Queued responses, less those that aborted
+    pub reward_mint: Option<Pubkey>,   // SPL mint held by the reward vault
+    pub reward_mode: RewardMode,       // How the per-response payout is determined
+    pub reward_per_response: u64,      // Reward (or cap) for each successful response
+    pub rewards_committed: u64,        // Vault balance reserved for in-flight responses
+    pub members: [SurveyMember; MAX_SURVEY_MEMBERS], // Roles granted besides the creator
+    pub member_count: u8,              // Occupied prefix of `members`
+    pub privacy_spent: u64,            // Epsilon (thousandths) consumed by releases so far
+    pub aggregate_accounts: u16,       // Analytics, tally, stats and cross-tab accounts still open
+    pub response_accounts: u64,        // Response and receipt accounts still open
+    pub cross_tab_questions: u32,      // Bit per question recorded only through its cross-tab
+    pub bump: u8,
+}
+
+impl Survey {
+    // The creator is always an owner; everyone else needs a membership entry.
+    pub fn role_of(&self, key: &Pubkey) -> Option<SurveyRole> {
+        if *key == self.creator {
+            return Some(SurveyRole::Owner);
+        }
+        self.members[..self.member_count as usize]
+            .iter()
+            .find(|member| member.key == *key)
+            .map(|member| member.role)
+    }
+
+    pub fn require_role(&self, key: &Pubkey, allowed: &[SurveyRole]) -> Result<()> {
+        let role = self.role_of(key).ok_or(ErrorCode::Unauthorized)?;
+        require!(allowed.contains(&role), ErrorCode::Unauthorized);
+        Ok(())
+    }
+
+    pub fn add_member(&mut self, member: SurveyMember) -> Result<()> {
+        require!(self.role_of(&member.key).is_none(), ErrorCode::AlreadySurveyMember);
+        let count = self.member_count as usize;
+        require!(count < MAX_SURVEY_MEMBERS, ErrorCode::TooManySurveyMembers);
+        self.members[count] = member;
+        self.member_count += 1;
+        Ok(())
+    }
+
+    // Swap-removes the member so the occupied prefix stays contiguous.
+    pub fn remove_member(&mut self, key: &Pubkey) -> Result<SurveyMember> {
+        let count = self.member_count as usize;
+        let index = self.members[..count]
+            .iter()
+            .position(|member| member.key == *key)
+            .ok_or(ErrorCode::NotSurveyMember)?;
+        let removed = self.members[index];
+        self.members[index] = self.members[count - 1];
+        self.members[count - 1] = SurveyMember::EMPTY;
+        self.member_count -= 1;
+        Ok(removed)
+    }
+
+    // Moves the survey to `next`, rejecting transitions the lifecycle does not allow:
+    // Draft -> Open, Open <-> Paused, and anything but Closed -> Closed.
+    pub fn transition(&mut self, next: SurveyStatus) -> Result<()> {
+        let allowed = matches!(
+            (self.status, next),
+            (SurveyStatus::Draft, SurveyStatus::Open)
+                | (SurveyStatus::Open, SurveyStatus::Paused)
+                | (SurveyStatus::Paused, SurveyStatus::Open)
+                | (SurveyStatus::Draft, SurveyStatus::Closed)
+                | (SurveyStatus::Open, SurveyStatus::Closed)
+                | (SurveyStatus::Paused, SurveyStatus::Closed)
+        );
+        require!(allowed, ErrorCode::InvalidSurveyStatus);
+
+        let now = Clock::get()?.unix_timestamp;
+        match next {
+            SurveyStatus::Open => self.opened_at = now,
+            SurveyStatus::Closed => self.closed_at = now,
+            _ => {}
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    // Rejects submissions outside the configured window or over the response cap.
+    // Runs before anything is queued so late responses never reach the MPC cluster.
+    pub fn check_accepting_responses(&self, now: i64) -> Result<()> {
+        require!(self.status == SurveyStatus::Open, ErrorCode::SurveyNotOpen);
+        if let Some(starts_at) = self.settings.starts_at {
+            require!(now >= starts_at, ErrorCode::SurveyNotStarted);
+        }
+        if let Some(ends_at) = self.settings.ends_at {
+            require!(now < ends_at, ErrorCode::SurveyEnded);
+        }
+        if let Some(max_responses) = self.settings.max_responses {
+            require!(
+                self.response_count < max_responses,
+                ErrorCode::MaxResponsesReached
+            );
+        }
+        Ok(())
+    }
+
+    // Private surveys only accept wallets that can prove membership in the allowlist.
+    pub fn check_allowlist(&self, respondent: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+        if let Some(root) = self.allowlist_root {
+            require!(
+                verify_allowlist_proof(root, respondent, proof),
+                ErrorCode::NotOnAllowlist
+            );
+        }
+        Ok(())
+    }
+
+    // Reserves `amount` of the vault for a queued response so concurrent
+    // submissions can never promise more than the vault holds.
+    pub fn commit_reward(&mut self, vault_balance: u64, amount: u64) -> Result<()> {
+        let available = vault_balance.saturating_sub(self.rewards_committed);
+        require!(available >= amount, ErrorCode::InsufficientRewardFunds);
+        self.rewards_committed = self
+            .rewards_committed
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    pub fn release_reward(&mut self, amount: u64) {
+        self.rewards_committed = self.rewards_committed.saturating_sub(amount);
+    }
+
+    // Analytics, tally, stats and cross-tab accounts are counted so the survey
+    // account outlives them: closing one requires proving the survey is closed.
+    pub fn open_aggregate(&mut self) -> Result<()> {
+        self.aggregate_accounts = self
+            .aggregate_accounts
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    // A question belongs to at most one cross-tab, and its answers are then
+    // recorded as a pair through that table only, so the table and the
+    // question's marginals cannot disagree.
+    pub fn bind_cross_tab(&mut self, row_question: u8, col_question: u8) -> Result<()> {
+        let bits = (1u32 << row_question) | (1u32 << col_question);
+        require!(
+            self.cross_tab_questions & bits == 0,
+            ErrorCode::QuestionInCrossTab
+        );
+        self.cross_tab_questions |= bits;
+        Ok(())
+    }
+
+    pub fn unbind_cross_tab(&mut self, row_question: u8, col_question: u8) {
+        self.cross_tab_questions &= !((1u32 << row_question) | (1u32 << col_question));
+    }
+
+    pub fn in_cross_tab(&self, question_index: u8) -> bool {
+        self.cross_tab_questions & 1u32.checked_shl(question_index as u32).unwrap_or(0) != 0
+    }
+
+    // Survey-scoped counterpart of require_owner_or_authority: any owner, not
+    // just the creator, or the config authority.
+    pub fn require_owner_or_config_authority(&self, signer: &Pubkey, config: &Config) -> Result<()> {
+        require!(
+            self.role_of(signer) == Some(SurveyRole::Owner) || *signer == config.authority,
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn close_aggregate(&mut self, signer: &Pubkey, config: &Config) -> Result<()> {
+        self.require_owner_or_config_authority(signer, config)?;
+        require!(
+            self.status == SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+        self.aggregate_accounts = self.aggregate_accounts.saturating_sub(1);
+        Ok(())
+    }
+
+    // Charges a release against the survey's privacy budget and returns what is
+    // left. The charge is taken at queue time and not refunded if the
+    // computation fails, which errs on the side of privacy.
+    pub fn spend_privacy(&mut self, epsilon_milli: u64) -> Result<u64> {
+        require!(epsilon_milli > 0, ErrorCode::InvalidPrivacyBudget);
+        let spent = self
+            .privacy_spent
+            .checked_add(epsilon_milli)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            spent <= self.settings.privacy_budget,
+            ErrorCode::PrivacyBudgetExhausted
+        );
+        self.privacy_spent = spent;
+        Ok(self.settings.privacy_budget - spent)
+    }
+}
+
+// Verifies a sorted-pair SHA-256 Merkle proof. Leaves are sha256(0x00 || pubkey)
+// and inner nodes sha256(0x01 || min(a, b) || max(a, b)), so clients can build the
+// tree without tracking left/right positions.
+pub fn verify_allowlist_proof(root: [u8; 32], respondent: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let mut node = hashv(&[&[0u8], respondent.as_ref()]).to_bytes();
+    for sibling in proof {
+        node = if node <= *sibling {
+            hashv(&[&[1u8], &node, sibling]).to_bytes()
+        } else {
+            hashv(&[&[1u8], sibling, &node]).to_bytes()
+        };
+    }
+    node == root
+}
+
+// One per (survey, respondent). Created with the wallet's first submission
+// and used to enforce the survey's per-wallet response limit.
+#[account]
+pub struct ResponseReceipt {
+    pub survey: Pubkey,                // Survey the receipt belongs to
+    pub respondent: Pubkey,            // Wallet that responded
+    pub response_count: u16,           // Responses queued by this wallet; next response sequence
+    pub failed_count: u16,             // Queued responses whose processing aborted
+    pub last_submitted_at: i64,        // Timestamp of the latest submission
+    pub bump: u8,
+}
+
+impl ResponseReceipt {
+    // An aborted response keeps its sequence number, since its account still
+    // exists, but no longer counts against the per-wallet limit.
+    pub fn counted_responses(&self) -> u16 {
+        self.response_count.saturating_sub(self.failed_count)
+    }
+}
+
+// `processed` is passed to compute_reward by offset, so it stays the first field.
+#[account]
+pub struct Response {
+    pub processed: [[u8; 32]; 4],      // ProcessedResponse sealed to the respondent
+    pub survey: Pubkey,                // Survey this response belongs to
+    pub respondent: Pubkey,            // Wallet that submitted the response
+    pub sequence: u16,                 // Per-wallet response number (PDA seed)
+    pub index: u64,                    // Position in the survey's response sequence
+    pub ciphertexts: [[u8; 32]; 5],    // Encrypted ResponseInput fields as submitted
+    pub nonce: [u8; 16],               // Encryption nonce
+    pub encryption_key: [u8; 32],      // Respondent's x25519 public key
+    pub processed_nonce: [u8; 16],     // Nonce of the sealed ProcessedResponse
+    pub loan_officer_key: [u8; 32],    // x25519 key the eligibility output is sealed to
+    pub eligibility: [[u8; 32]; 3],    // LoanEligibility sealed to the loan officer
+    pub eligibility_nonce: [u8; 16],   // Nonce of the sealed LoanEligibility
+    pub submitted_slot: u64,           // Slot the response was queued in
+    pub computation_offset: u64,       // Offset of the processing computation
+    pub status: ComputationStatus,
+    pub queued_at: i64,                // Latest processing or reward computation queued for it
+    pub reward_account: Pubkey,        // Token account the reward is paid to
+    pub reward_mode: RewardMode,       // Survey reward mode when the response was submitted
+    pub reward_amount: u64,            // Reward reserved for this response
+    pub reward_settled: bool,          // Payout is final; pay_response_reward may transfer it
+    pub reward_paid: bool,
+    pub reward_claim_pending: bool,    // A quality-weighted reward computation is queued
+    pub recorded_choices: u32,         // Bit per question whose choice was tallied or cross-tabulated
+    pub recorded_ratings: u32,         // Bit per question whose rating was accumulated
+    pub bump: u8,
+}
+
+impl Response {
+    // Question bits in `recorded_choices` and `recorded_ratings` are claimed
+    // when a record computation is queued, so one answer cannot be queued twice,
+    // and released again if the computation aborts or rejects the answer.
+    pub fn claim_record(mask: &mut u32, question_index: u8, recorded: ErrorCode) -> Result<()> {
+        let bit = 1u32
+            .checked_shl(question_index as u32)
+            .ok_or(ErrorCode::InvalidQuestionIndex)?;
+        require!(*mask & bit == 0, recorded);
+        *mask |= bit;
+        Ok(())
+    }
+
+    pub fn release_record(mask: &mut u32, question_index: u8) {
+        *mask &= !1u32.checked_shl(question_index as u32).unwrap_or(0);
+    }
+}
+
+// Shared tail of the record_* callbacks: completes or fails the computation
+// record and gives the question bits back unless the answers were counted, so
+// an aborted or rejected record can be retried.
+fn settle_record(
+    computation_result: &mut ComputationResult,
+    output_nonce: Option<u128>,
+    accepted: bool,
+    mask: &mut u32,
+    question_indices: &[u8],
+) -> Result<()> {
+    match output_nonce {
+        Some(nonce) => computation_result.record_success(None, nonce)?,
+        None => computation_result.record_failure(FailureReason::Aborted)?,
+    }
+    if output_nonce.is_none() || !accepted {
+        for &question_index in question_indices {
+            Response::release_record(mask, question_index);
+        }
+    }
+    Ok(())
+}
+
+// Running encrypted aggregates for a survey. The state is only decryptable by
+// the MXE and is fed back into every submit_response computation.
+#[account]
+pub struct SurveyAnalytics {
+    pub analytics_state: [[u8; 32]; 4], // Enc<Mxe, SurveyAnalytics> ciphertexts
+    pub nonce: u128,                    // Nonce of the MXE-encrypted state
+    pub survey: Pubkey,                 // Survey these aggregates belong to
+    pub payer: Pubkey,                  // Receives the rent when the account is closed
+    pub is_initialized: bool,           // Set once the init computation lands
+    pub bump: u8,
+}
+
+// Encrypted option histogram for one multiple-choice question. Like
+// SurveyAnalytics, only the MXE can decrypt it.
+#[account]
+pub struct ChoiceTally {
+    pub counts: [[u8; 32]; MAX_CHOICE_OPTIONS], // Enc<Mxe, [u64; MAX_CHOICE_OPTIONS]> ciphertexts
+    pub nonce: u128,                   // Nonce of the MXE-encrypted histogram
+    pub survey: Pubkey,
+    pub payer: Pubkey,                 // Receives the rent when the account is closed
+    pub question_index: u8,            // Question within the survey schema (PDA seed)
+    pub option_count: u8,              // Options offered by the question
+    pub is_initialized: bool,          // Set once the init computation lands
+    pub bump: u8,
+}
+
+// Encrypted count, sum and sum of squares for one rating question
+#[account]
+pub struct RatingStats {
+    pub stats: [[u8; 32]; 3],          // Enc<Mxe, RatingStats> ciphertexts
+    pub nonce: u128,                   // Nonce of the MXE-encrypted accumulators
+    pub survey: Pubkey,
+    pub payer: Pubkey,                 // Receives the rent when the account is closed
+    pub question_index: u8,            // Question within the survey schema (PDA seed)
+    pub is_initialized: bool,          // Set once the init computation lands
+    pub bump: u8,
+}
+
+// Encrypted contingency table between two questions of a survey, row-major
+#[account]
+pub struct CrossTab {
+    pub table: [[u8; 32]; CROSS_TAB_CELLS], // Enc<Mxe, [[u64; COLS]; ROWS]> ciphertexts
+    pub nonce: u128,                   // Nonce of the MXE-encrypted table
+    pub survey: Pubkey,
+    pub payer: Pubkey,                 // Receives the rent when the account is closed
+    pub table_index: u8,               // Table within the survey (PDA seed)
+    pub row_question: u8,              // Question whose options index the rows
+    pub col_question: u8,              // Question whose options index the columns
+    pub row_options: u8,               // Options offered by the row question
+    pub col_options: u8,               // Options offered by the column question
+    pub min_cell_size: u64,            // Cells whose noised count is lower are suppressed on reveal
+    pub is_initialized: bool,          // Set once the init computation lands
+    pub bump: u8,
+}
+
+// Quiz whose answer key is only ever held encrypted to the MXE
+#[account]
+pub struct Quiz {
+    pub answer_key: [[u8; 32]; 2 * MAX_QUIZ_QUESTIONS], // Enc<Mxe, AnswerKey>: answers, then points
+    pub answer_key_nonce: u128,        // Nonce of the MXE-encrypted answer key
+    pub creator: Pubkey,
+    pub quiz_id: u64,                  // Creator-chosen id (PDA seed)
+    pub question_count: u8,            // Questions in use, at most MAX_QUIZ_QUESTIONS
+    pub minimum_score: u64,            // Passing score; compared to the score inside MPC
+    pub whitelist_validity: Option<i64>, // Seconds a whitelist stays valid; None never expires
+    pub max_attempts: u16,             // Attempts allowed per wallet
+    pub time_limit: Option<i64>,       // Seconds from start to submission; None is untimed
+    pub key_uploaded: bool,            // Set once the upload computation lands
+    pub key_upload_pending: bool,      // An upload computation is queued
+    pub created_at: i64,
+    pub last_queued_at: i64,           // Latest upload or grading queued against the quiz
+    pub open_accounts: u32,            // Attempts and whitelists still open
+    pub bump: u8,
+}
+
+impl Quiz {
+    // Attempts and whitelists are counted so they are closed before the quiz:
+    // their PDAs only hang off the quiz address, so leftovers would otherwise
+    // carry over to a quiz recreated under the same id.
+    pub fn open_account(&mut self) -> Result<()> {
+        self.open_accounts = self
+            .open_accounts
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    // No grading callback can still write to the quiz's accounts
+    pub fn is_idle(&self, now: i64) -> bool {
+        now - self.last_queued_at >= STALE_COMPUTATION_SECS
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum QuizAttemptStatus {
+    NotStarted, // Freshly allocated; what a zeroed account deserializes to
+    InProgress, // Started and not yet submitted
+    Grading,    // Submitted; the grading computation is queued
+    Graded,     // Score of the latest submission is available
+    Failed,     // Grading of the latest submission was aborted
+}
+
+// A participant's attempts at a quiz; score fields hold the latest graded one
+#[account]
+pub struct QuizAttempt {
+    pub quiz: Pubkey,
+    pub participant: Pubkey,
+    pub attempt_count: u16,            // Attempts started so far
+    pub started_at: i64,               // Clock timestamp of the current attempt
+    pub status: QuizAttemptStatus,
+    pub encryption_key: [u8; 32],      // Participant key the score is sealed to
+    pub score: [u8; 32],               // Enc<Shared, u64> score
+    pub score_nonce: [u8; 16],
+    pub graded_at: i64,
+    pub bump: u8,
+}
+
+// Proof that `wallet` passed `quiz`. The account is allocated when the attempt
+// is queued and only becomes valid once the grading callback sets `issued`.
+#[account]
+pub struct Whitelist {
+    pub quiz: Pubkey,
+    pub wallet: Pubkey,
+    pub issued: bool,                  // Set by the grading callback on a passing score
+    pub issued_at: i64,
+    pub expires_at: Option<i64>,       // None never expires
+    pub bump: u8,
+}
+
+impl Whitelist {
+    pub fn is_valid(&self, now: i64) -> bool {
+        self.issued && !matches!(self.expires_at, Some(expires_at) if now >= expires_at)
+    }
+}
+
+// Program-wide admin settings. A singleton PDA created once by the upgrade
+// authority; every non-callback instruction checks `paused`. Callbacks are not
+// gated so computations queued before a pause can still settle, and survey
+// owners can still pause or close their surveys during an incident.
+#[account]
+pub struct Config {
+    pub authority: Pubkey,          // Admin allowed to pause, set fees and init comp defs
+    pub pending_authority: Option<Pubkey>, // Proposed admin; takes over once it accepts
+    pub paused: bool,               // Emergency stop for all user-facing instructions
+    pub protocol_fee_lamports: u64, // Charged to the creator on create_survey
+    pub treasury: Pubkey,           // Receives protocol fees
+    pub bump: u8,
 }
 
 declare_id!("HhfT3ytQx3CsvR354wcPRGB4m7sKQ7Xhpcx4VZ1QiGaR");
@@ -62,7 +808,23 @@ pub mod survey_x {
             Argument::EncryptedU8(ciphertext_0),
             Argument::EncryptedU8(ciphertext_1),
         ];
-        queue_computation(ctx.accounts, computation_offset, args, vec![], None)?;
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::AddTogether,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![CallbackAccount {
+                pubkey: ctx.accounts.computation_result.key(),
+                is_writable: true,
+            }],
+            None,
+        )?;
         Ok(())
     }
 
@@ -73,23 +835,20 @@ pub mod survey_x {
     ) -> Result<()> {
         let o = match output {
             ComputationOutputs::Success(AddTogetherOutput { field_0: o }) => o,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
         };
 
         // Store computation result persistently
         let computation_result = &mut ctx.accounts.computation_result;
-        let clock = Clock::get()?;
-
-        computation_result.computation_id = [0u8; 32]; // Generate unique ID based on context
-        computation_result.computation_type = ComputationType::AddTogether;
         computation_result.status = ComputationStatus::Completed;
         computation_result.result_data = o.ciphertexts[0];
         computation_result.nonce = o.nonce.to_le_bytes();
-        computation_result.created_at = clock.unix_timestamp;
-        computation_result.completed_at = clock.unix_timestamp;
-        computation_result.creator = *ctx.accounts.payer.key;
-        computation_result.survey_id = None;
-        computation_result.response_id = None;
+        computation_result.completed_at = Clock::get()?.unix_timestamp;
 
         emit!(SumEvent {
             sum: o.ciphertexts[0],
@@ -109,19 +868,174 @@ pub mod survey_x {
     }
 
 
+    #[allow(clippy::too_many_arguments)]
     pub fn submit_response(
         ctx: Context<SubmitResponse>,
         computation_offset: u64,
-        ciphertext: [u8; 32],
+        response_ciphertext: [u8; 32],
+        rating_ciphertext: [u8; 32],
+        feedback_length_ciphertext: [u8; 32],
+        category_ciphertext: [u8; 32],
+        timestamp_ciphertext: [u8; 32],
         pub_key: [u8; 32],
         nonce: u128,
+        loan_officer_pub_key: [u8; 32],
+        loan_officer_nonce: u128,
+        allowlist_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
+        let clock = Clock::get()?;
+        ctx.accounts
+            .survey
+            .check_accepting_responses(clock.unix_timestamp)?;
+        ctx.accounts
+            .survey
+            .check_allowlist(&ctx.accounts.payer.key(), &allowlist_proof)?;
+        if let Some(gate) = ctx.accounts.survey.settings.gate {
+            gate.check(
+                &ctx.accounts.payer.key(),
+                ctx.accounts.gate_token_account.as_deref().map(|a| &**a),
+                ctx.accounts.gate_metadata.as_deref().map(|a| &**a),
+                ctx.accounts.gate_whitelist.as_deref().map(|a| &**a),
+                clock.unix_timestamp,
+            )?;
+        }
+
+        let per_wallet_limit = ctx.accounts.survey.settings.response_mode.per_wallet_limit();
+        let receipt = &mut ctx.accounts.response_receipt;
+        require!(
+            receipt.counted_responses() < per_wallet_limit,
+            ErrorCode::ResponseLimitReached
+        );
+        let sequence = receipt.response_count;
+        // Like aggregates, responses and receipts are counted so the survey
+        // account outlives them; the receipt is new on the wallet's first response
+        let opened_accounts = if sequence == 0 { 2 } else { 1 };
+        receipt.survey = ctx.accounts.survey.key();
+        receipt.respondent = ctx.accounts.payer.key();
+        receipt.response_count = receipt
+            .response_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        receipt.last_submitted_at = clock.unix_timestamp;
+        receipt.bump = ctx.bumps.response_receipt;
+
+        let reward_amount = ctx.accounts.survey.reward_per_response;
+        let mut reward_account = Pubkey::default();
+        if reward_amount > 0 {
+            let vault = ctx
+                .accounts
+                .reward_vault
+                .as_ref()
+                .ok_or(ErrorCode::MissingRewardAccount)?;
+            let destination = ctx
+                .accounts
+                .respondent_reward_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingRewardAccount)?;
+            require!(ctx.accounts.token_program.is_some(), ErrorCode::MissingRewardAccount);
+            require_keys_eq!(destination.owner, ctx.accounts.payer.key(), ErrorCode::InvalidRewardAccount);
+            require_keys_eq!(destination.mint, vault.mint, ErrorCode::InvalidRewardAccount);
+            reward_account = destination.key();
+            let vault_balance = vault.amount;
+            ctx.accounts.survey.commit_reward(vault_balance, reward_amount)?;
+        }
+
         let args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext),
+            Argument::EncryptedU64(response_ciphertext),
+            Argument::EncryptedU8(rating_ciphertext),
+            Argument::EncryptedU16(feedback_length_ciphertext),
+            Argument::EncryptedU8(category_ciphertext),
+            Argument::EncryptedU64(timestamp_ciphertext),
+            Argument::PlaintextU128(ctx.accounts.survey_analytics.nonce),
+            // analytics_state sits right after the account discriminator
+            Argument::Account(ctx.accounts.survey_analytics.key(), 8, 32 * 4),
+            Argument::ArcisPubkey(loan_officer_pub_key),
+            Argument::PlaintextU128(loan_officer_nonce),
+            Argument::PlaintextU64(clock.unix_timestamp as u64),
         ];
-        queue_computation(ctx.accounts, computation_offset, args, vec![], None)?;
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::SubmitResponse,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+        ctx.accounts.computation_result.response_id = Some(ctx.accounts.response.key().to_bytes());
+
+        let response = &mut ctx.accounts.response;
+        response.survey = ctx.accounts.survey.key();
+        response.respondent = ctx.accounts.payer.key();
+        response.sequence = sequence;
+        response.index = ctx.accounts.survey.response_count;
+        response.ciphertexts = [
+            response_ciphertext,
+            rating_ciphertext,
+            feedback_length_ciphertext,
+            category_ciphertext,
+            timestamp_ciphertext,
+        ];
+        response.nonce = nonce.to_le_bytes();
+        response.encryption_key = pub_key;
+        response.processed = [[0u8; 32]; 4];
+        response.processed_nonce = [0u8; 16];
+        response.loan_officer_key = loan_officer_pub_key;
+        response.eligibility = [[0u8; 32]; 3];
+        response.eligibility_nonce = [0u8; 16];
+        response.submitted_slot = clock.slot;
+        response.computation_offset = computation_offset;
+        response.status = ComputationStatus::Pending;
+        response.queued_at = clock.unix_timestamp;
+        response.reward_account = reward_account;
+        response.reward_mode = ctx.accounts.survey.reward_mode;
+        response.reward_amount = reward_amount;
+        response.reward_settled = false;
+        response.reward_paid = false;
+        response.reward_claim_pending = false;
+        response.recorded_choices = 0;
+        response.recorded_ratings = 0;
+        response.bump = ctx.bumps.response;
+
+        let survey = &mut ctx.accounts.survey;
+        survey.response_count = survey
+            .response_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        survey.response_accounts = survey
+            .response_accounts
+            .checked_add(opened_accounts)
+            .ok_or(ErrorCode::Overflow)?;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.response.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.survey_analytics.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.survey.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.response_receipt.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
         Ok(())
     }
 
@@ -132,46 +1046,171 @@ pub mod survey_x {
     ) -> Result<()> {
         let o = match output {
             ComputationOutputs::Success(SubmitResponseOutput { field_0: o }) => o,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                ctx.accounts.response.status = ComputationStatus::Failed;
+                let reward_amount = ctx.accounts.response.reward_amount;
+                let survey = &mut ctx.accounts.survey;
+                survey.release_reward(reward_amount);
+                // Give the response slot back, both to the wallet's limit and
+                // to the survey's max_responses, so the respondent can retry
+                survey.response_count = survey.response_count.saturating_sub(1);
+                let receipt = &mut ctx.accounts.response_receipt;
+                receipt.failed_count = receipt.failed_count.saturating_add(1);
+                return Ok(());
+            }
         };
 
         // Store computation result persistently
         let computation_result = &mut ctx.accounts.computation_result;
-        let clock = Clock::get()?;
-
-        computation_result.computation_id = [0u8; 32]; // Generate unique ID based on context
-        computation_result.computation_type = ComputationType::SubmitResponse;
         computation_result.status = ComputationStatus::Completed;
-        computation_result.result_data = o.field_0.ciphertexts[0]; // ProcessedResponse
+        computation_result.result_data = o.field_0.ciphertexts[0]; // ProcessedResponse.score (full output on Response)
         computation_result.nonce = o.field_0.nonce.to_le_bytes();
-        computation_result.created_at = clock.unix_timestamp;
-        computation_result.completed_at = clock.unix_timestamp;
-        computation_result.creator = *ctx.accounts.payer.key;
-        computation_result.survey_id = None; // TODO: Link to actual survey
-        computation_result.response_id = Some([0u8; 32]); // TODO: Generate proper response ID
-
-        // Handle tuple return: (Enc<Shared, ProcessedResponse>, Enc<Shared, LoanEligibility>)
-        // Access the first result (ProcessedResponse) from the tuple
+        computation_result.completed_at = Clock::get()?.unix_timestamp;
+
+        // Handle tuple return:
+        // (Enc<Shared, ProcessedResponse>, Enc<Shared, LoanEligibility>, Enc<Mxe, SurveyAnalytics>)
+        // field_0 is sealed to the respondent, field_1 to the loan officer and
+        // field_2 is the updated running analytics, written back for the next submission
+        let survey_analytics = &mut ctx.accounts.survey_analytics;
+        survey_analytics.analytics_state = o.field_2.ciphertexts;
+        survey_analytics.nonce = o.field_2.nonce;
+
+        let response = &mut ctx.accounts.response;
+        response.status = ComputationStatus::Completed;
+        response.processed = o.field_0.ciphertexts;
+        response.processed_nonce = o.field_0.nonce.to_le_bytes();
+        response.eligibility = o.field_1.ciphertexts;
+        response.eligibility_nonce = o.field_1.nonce.to_le_bytes();
+
+        // A fixed reward becomes payable once the response was processed
+        // successfully. The transfer itself happens in pay_response_reward, so a
+        // closed or frozen respondent account can never revert this callback;
+        // quality-weighted rewards stay reserved until claim_response_reward runs
+        if response.reward_amount > 0 && response.reward_mode == RewardMode::Fixed {
+            response.reward_settled = true;
+        }
+
         emit!(ResponseEvent {
-            response: o.field_0.ciphertexts[0],
+            response: o.field_0.ciphertexts,
             nonce: o.field_0.nonce.to_le_bytes(),
+            recipient: o.field_0.encryption_key,
+        });
+        emit!(LoanEligibilityEvent {
+            response_account: response.key(),
+            eligibility: o.field_1.ciphertexts,
+            nonce: o.field_1.nonce.to_le_bytes(),
+            recipient: o.field_1.encryption_key,
+        });
+        emit!(SurveyAnalyticsUpdatedEvent {
+            survey: ctx.accounts.survey_analytics.survey,
+            analytics: o.field_2.ciphertexts,
+            nonce: o.field_2.nonce.to_le_bytes(),
         });
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_survey(
         ctx: Context<CreateSurvey>,
         computation_offset: u64,
-        ciphertext: [u8; 32],
+        survey_id: u64,
+        schema_hash: [u8; 32],
+        settings: SurveySettings,
+        title_length_ciphertext: [u8; 32],
+        description_length_ciphertext: [u8; 32],
+        question_count_ciphertext: [u8; 32],
+        complexity_score_ciphertext: [u8; 32],
+        target_audience_ciphertext: [u8; 32],
+        estimated_duration_ciphertext: [u8; 32],
         pub_key: [u8; 32],
         nonce: u128,
+        analyst_pub_key: [u8; 32],
+        analyst_nonce: u128,
     ) -> Result<()> {
+        settings.validate()?;
+
+        let protocol_fee = ctx.accounts.config.protocol_fee_lamports;
+        if protocol_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                protocol_fee,
+            )?;
+        }
+
         let args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext),
+            Argument::EncryptedU8(title_length_ciphertext),
+            Argument::EncryptedU8(description_length_ciphertext),
+            Argument::EncryptedU8(question_count_ciphertext),
+            Argument::EncryptedU8(complexity_score_ciphertext),
+            Argument::EncryptedU8(target_audience_ciphertext),
+            Argument::EncryptedU16(estimated_duration_ciphertext),
+            Argument::ArcisPubkey(analyst_pub_key),
+            Argument::PlaintextU128(analyst_nonce),
         ];
-        queue_computation(ctx.accounts, computation_offset, args, vec![], None)?;
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::CreateSurvey,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        let survey = &mut ctx.accounts.survey;
+        survey.creator = ctx.accounts.payer.key();
+        survey.survey_id = survey_id;
+        survey.schema_hash = schema_hash;
+        survey.metrics = [[0u8; 32]; 6];
+        survey.metrics_nonce = [0u8; 16];
+        survey.analyst_key = analyst_pub_key;
+        survey.analysis = [[0u8; 32]; 4];
+        survey.analysis_nonce = [0u8; 16];
+        survey.settings = settings;
+        survey.allowlist_root = None;
+        survey.reward_mint = None;
+        survey.reward_mode = RewardMode::Fixed;
+        survey.reward_per_response = 0;
+        survey.rewards_committed = 0;
+        survey.members = [SurveyMember::EMPTY; MAX_SURVEY_MEMBERS];
+        survey.member_count = 0;
+        survey.status = SurveyStatus::Draft;
+        survey.created_at = Clock::get()?.unix_timestamp;
+        survey.opened_at = 0;
+        survey.closed_at = 0;
+        survey.response_count = 0;
+        survey.privacy_spent = 0;
+        survey.aggregate_accounts = 0;
+        survey.response_accounts = 0;
+        survey.cross_tab_questions = 0;
+        survey.bump = ctx.bumps.survey;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.survey.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
         Ok(())
     }
 
@@ -182,113 +1221,4579 @@ pub mod survey_x {
     ) -> Result<()> {
         let o = match output {
             ComputationOutputs::Success(CreateSurveyOutput { field_0: o }) => o,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
         };
 
         // Store computation result persistently
         let computation_result = &mut ctx.accounts.computation_result;
-        let clock = Clock::get()?;
-
-        computation_result.computation_id = [0u8; 32]; // Generate unique ID based on context
-        computation_result.computation_type = ComputationType::CreateSurvey;
         computation_result.status = ComputationStatus::Completed;
-        computation_result.result_data = o.field_0.ciphertexts[0]; // SurveyMetrics
+        computation_result.result_data = o.field_0.ciphertexts[4]; // SurveyMetrics.total_score (full output on Survey)
         computation_result.nonce = o.field_0.nonce.to_le_bytes();
-        computation_result.created_at = clock.unix_timestamp;
-        computation_result.completed_at = clock.unix_timestamp;
-        computation_result.creator = *ctx.accounts.payer.key;
-        computation_result.survey_id = Some([0u8; 32]); // TODO: Generate proper survey ID
-        computation_result.response_id = None;
+        computation_result.completed_at = Clock::get()?.unix_timestamp;
 
         // Handle tuple return: (Enc<Shared, SurveyMetrics>, Enc<Shared, SurveyAnalysis>)
-        // Access the first result (SurveyMetrics) from the tuple
+        // field_0 is sealed to the creator, field_1 to the survey analyst
+        let survey = &mut ctx.accounts.survey;
+        survey.metrics = o.field_0.ciphertexts;
+        survey.metrics_nonce = o.field_0.nonce.to_le_bytes();
+        survey.analysis = o.field_1.ciphertexts;
+        survey.analysis_nonce = o.field_1.nonce.to_le_bytes();
+
         emit!(SurveyCreatedEvent {
-            survey_id: o.field_0.ciphertexts[0],
+            survey: survey.key(),
+            metrics: o.field_0.ciphertexts,
             nonce: o.field_0.nonce.to_le_bytes(),
+            recipient: o.field_0.encryption_key,
+        });
+        emit!(SurveyAnalysisEvent {
+            survey: survey.key(),
+            analysis: o.field_1.ciphertexts,
+            nonce: o.field_1.nonce.to_le_bytes(),
+            recipient: o.field_1.encryption_key,
         });
         Ok(())
     }
-}
 
-#[queue_computation_accounts("add_together", payer)]
-#[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct AddTogether<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: This is a PDA managed by the Arcium program and validated there.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: This is a PDA managed by the Arcium program and validated there.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: This is a PDA managed by the Arcium program and validated there.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
-}
+    pub fn open_survey(ctx: Context<UpdateSurveyStatus>) -> Result<()> {
+        ctx.accounts
+            .survey
+            .require_role(&ctx.accounts.authority.key(), &[SurveyRole::Owner])?;
+        require!(
+            ctx.accounts.survey.status == SurveyStatus::Draft,
+            ErrorCode::InvalidSurveyStatus
+        );
+        set_survey_status(&mut ctx.accounts.survey, SurveyStatus::Open)
+    }
 
-#[callback_accounts("add_together", payer)]
-#[derive(Accounts)]
-pub struct AddTogetherCallback<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        init,
-        payer = payer,
+    pub fn pause_survey(ctx: Context<StopSurvey>) -> Result<()> {
+        ctx.accounts.survey.require_role(
+            &ctx.accounts.authority.key(),
+            &[SurveyRole::Owner, SurveyRole::Editor],
+        )?;
+        set_survey_status(&mut ctx.accounts.survey, SurveyStatus::Paused)
+    }
+
+    pub fn resume_survey(ctx: Context<UpdateSurveyStatus>) -> Result<()> {
+        ctx.accounts.survey.require_role(
+            &ctx.accounts.authority.key(),
+            &[SurveyRole::Owner, SurveyRole::Editor],
+        )?;
+        require!(
+            ctx.accounts.survey.status == SurveyStatus::Paused,
+            ErrorCode::InvalidSurveyStatus
+        );
+        set_survey_status(&mut ctx.accounts.survey, SurveyStatus::Open)
+    }
+
+    pub fn close_survey(ctx: Context<StopSurvey>) -> Result<()> {
+        ctx.accounts
+            .survey
+            .require_role(&ctx.accounts.authority.key(), &[SurveyRole::Owner])?;
+        set_survey_status(&mut ctx.accounts.survey, SurveyStatus::Closed)
+    }
+
+    // Pushes back the end of the response window and/or raises the response cap.
+    // Settings can only be loosened here, never tightened under respondents.
+    pub fn extend_survey(
+        ctx: Context<UpdateSurveyStatus>,
+        ends_at: Option<i64>,
+        max_responses: Option<u64>,
+    ) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        survey.require_role(
+            &ctx.accounts.authority.key(),
+            &[SurveyRole::Owner, SurveyRole::Editor],
+        )?;
+        require!(
+            survey.status != SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+        let later = match (survey.settings.ends_at, ends_at) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(current), Some(next)) => next >= current,
+        };
+        let larger = match (survey.settings.max_responses, max_responses) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(current), Some(next)) => next >= current,
+        };
+        require!(later && larger, ErrorCode::InvalidSurveyExtension);
+
+        survey.settings.ends_at = ends_at;
+        survey.settings.max_responses = max_responses;
+        survey.settings.validate()?;
+
+        emit!(SurveyExtendedEvent {
+            survey: survey.key(),
+            ends_at,
+            max_responses,
+        });
+        Ok(())
+    }
+
+    // Grants a role on the survey. Only owners manage membership.
+    pub fn add_survey_member(
+        ctx: Context<UpdateSurveyMembers>,
+        member: Pubkey,
+        role: SurveyRole,
+        encryption_key: [u8; 32],
+    ) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        survey.require_role(&ctx.accounts.authority.key(), &[SurveyRole::Owner])?;
+        survey.add_member(SurveyMember {
+            key: member,
+            role,
+            encryption_key,
+        })?;
+
+        emit!(SurveyMemberAddedEvent {
+            survey: survey.key(),
+            member,
+            role,
+        });
+        Ok(())
+    }
+
+    // Revokes a role. Owners may remove anyone but the creator; any member may
+    // remove themselves.
+    pub fn remove_survey_member(ctx: Context<UpdateSurveyMembers>, member: Pubkey) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        let authority = ctx.accounts.authority.key();
+        if authority != member {
+            survey.require_role(&authority, &[SurveyRole::Owner])?;
+        }
+        let removed = survey.remove_member(&member)?;
+
+        emit!(SurveyMemberRemovedEvent {
+            survey: survey.key(),
+            member,
+            role: removed.role,
+        });
+        Ok(())
+    }
+
+    // Sets or clears the respondent allowlist. Only allowed before the survey opens
+    // or while it is paused, so the root never changes under in-flight submissions.
+    pub fn set_allowlist_root(
+        ctx: Context<UpdateSurveyAllowlist>,
+        allowlist_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        survey.require_role(&ctx.accounts.authority.key(), &[SurveyRole::Owner])?;
+        require!(
+            matches!(survey.status, SurveyStatus::Draft | SurveyStatus::Paused),
+            ErrorCode::InvalidSurveyStatus
+        );
+        survey.allowlist_root = allowlist_root;
+
+        emit!(AllowlistRootUpdatedEvent {
+            survey: survey.key(),
+            allowlist_root,
+        });
+        Ok(())
+    }
+
+    // Read-only: emits a receipt for a completed computation of the survey.
+    // Owners, analysts and auditors may request one; nothing is decrypted.
+    pub fn request_result_receipt(ctx: Context<RequestResultReceipt>) -> Result<()> {
+        let survey = &ctx.accounts.survey;
+        let requested_by = ctx.accounts.authority.key();
+        survey.require_role(
+            &requested_by,
+            &[SurveyRole::Owner, SurveyRole::Analyst, SurveyRole::Auditor],
+        )?;
+        let computation_result = &ctx.accounts.computation_result;
+        require!(
+            computation_result.survey_id == Some(survey.key().to_bytes()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            computation_result.status == ComputationStatus::Completed,
+            ErrorCode::ComputationNotCompleted
+        );
+
+        emit!(ResultReceiptEvent {
+            survey: survey.key(),
+            computation_offset: computation_result.computation_offset,
+            computation_type: computation_result.computation_type,
+            result_hash: computation_result.receipt_hash(),
+            completed_at: computation_result.completed_at,
+            requested_by,
+        });
+        Ok(())
+    }
+
+    // Deposits reward tokens into the survey's escrow vault and sets the
+    // per-response reward. The vault mint is fixed by the first deposit. Mode
+    // and rate changes only apply to later submissions; each response keeps
+    // the mode and amount it was submitted under.
+    pub fn fund_reward_vault(
+        ctx: Context<FundRewardVault>,
+        reward_mode: RewardMode,
+        reward_per_response: u64,
+        amount: u64,
+    ) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        require!(
+            survey.status != SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+        let mint = ctx.accounts.reward_mint.key();
+        if let Some(reward_mint) = survey.reward_mint {
+            require_keys_eq!(reward_mint, mint, ErrorCode::InvalidRewardAccount);
+        }
+        survey.reward_mint = Some(mint);
+        survey.reward_mode = reward_mode;
+        survey.reward_per_response = reward_per_response;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority_token_account.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(RewardVaultFundedEvent {
+            survey: survey.key(),
+            mint,
+            amount,
+            reward_per_response,
+        });
+        Ok(())
+    }
+
+    // Returns every token not reserved for in-flight responses to an owner-chosen account.
+    pub fn withdraw_reward_vault(ctx: Context<WithdrawRewardVault>) -> Result<()> {
+        require!(
+            ctx.accounts.survey.status == SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+        let amount = ctx
+            .accounts
+            .reward_vault
+            .amount
+            .saturating_sub(ctx.accounts.survey.rewards_committed);
+        transfer_from_vault(
+            &ctx.accounts.survey,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.destination,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        emit!(RewardVaultWithdrawnEvent {
+            survey: ctx.accounts.survey.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn init_compute_reward_comp_def(ctx: Context<InitComputeRewardCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    // Queues the quality-weighted payout computation for a processed response.
+    // Anyone may trigger it; the callback settles the amount and
+    // pay_response_reward sends it to the respondent's recorded account.
+    pub fn claim_response_reward(
+        ctx: Context<ClaimResponseReward>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let response = &ctx.accounts.response;
+        require!(
+            response.reward_mode == RewardMode::QualityWeighted,
+            ErrorCode::InvalidRewardMode
+        );
+        require!(
+            response.status == ComputationStatus::Completed,
+            ErrorCode::ResponseNotCompleted
+        );
+        require!(
+            response.reward_amount > 0
+                && !response.reward_settled
+                && !response.reward_paid
+                && !response.reward_claim_pending,
+            ErrorCode::RewardAlreadyClaimed
+        );
+
+        let args = vec![
+            Argument::ArcisPubkey(response.encryption_key),
+            Argument::PlaintextU128(u128::from_le_bytes(response.processed_nonce)),
+            Argument::Account(response.key(), RESPONSE_PROCESSED_OFFSET, 32 * 4),
+            Argument::PlaintextU64(response.reward_amount),
+        ];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::ComputeReward,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+        ctx.accounts.computation_result.response_id = Some(ctx.accounts.response.key().to_bytes());
+        ctx.accounts.response.reward_claim_pending = true;
+        ctx.accounts.response.queued_at = Clock::get()?.unix_timestamp;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.response.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.survey.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "compute_reward")]
+    pub fn compute_reward_callback(
+        ctx: Context<ComputeRewardCallback>,
+        output: ComputationOutputs<ComputeRewardOutput>,
+    ) -> Result<()> {
+        let payout = match output {
+            ComputationOutputs::Success(ComputeRewardOutput { field_0 }) => field_0,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                // Leave the reward reserved so the claim can be retried
+                ctx.accounts.response.reward_claim_pending = false;
+                return Ok(());
+            }
+        };
+
+        let computation_result = &mut ctx.accounts.computation_result;
+        computation_result.status = ComputationStatus::Completed;
+        computation_result.completed_at = Clock::get()?.unix_timestamp;
+
+        // A reward settles once; the first payout's reservation may already be
+        // released and paid, so a later result must not touch it
+        let response = &mut ctx.accounts.response;
+        response.reward_claim_pending = false;
+        if response.reward_settled {
+            return Ok(());
+        }
+
+        // The circuit caps the payout already; clamp again so a bad output can
+        // never spend more than was reserved for this response. Only the payout
+        // stays reserved; pay_response_reward transfers it, so a closed or frozen
+        // respondent account cannot revert this callback
+        let reserved = response.reward_amount;
+        let payout = payout.min(reserved);
+        ctx.accounts.survey.release_reward(reserved - payout);
+        response.reward_amount = payout;
+        response.reward_settled = true;
+        if payout == 0 {
+            response.reward_paid = true;
+        }
+
+        emit!(RewardSettledEvent {
+            survey: ctx.accounts.survey.key(),
+            respondent: response.respondent,
+            amount: payout,
+        });
+        Ok(())
+    }
+
+    // Transfers a settled reward to the respondent's recorded token account.
+    // Anyone may trigger it. A failed transfer only fails this instruction and
+    // leaves the reward reserved, so it can be retried once the account is usable.
+    pub fn pay_response_reward(ctx: Context<PayResponseReward>) -> Result<()> {
+        let response = &mut ctx.accounts.response;
+        require!(
+            response.reward_settled && !response.reward_claim_pending,
+            ErrorCode::RewardNotSettled
+        );
+        require!(!response.reward_paid, ErrorCode::RewardAlreadyClaimed);
+
+        let amount = response.reward_amount;
+        transfer_from_vault(
+            &ctx.accounts.survey,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.respondent_reward_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        ctx.accounts.survey.release_reward(amount);
+        response.reward_paid = true;
+
+        emit!(RewardPaidEvent {
+            survey: ctx.accounts.survey.key(),
+            respondent: response.respondent,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn init_survey_analytics_comp_def(
+        ctx: Context<InitSurveyAnalyticsCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_survey_analytics(
+        ctx: Context<InitSurveyAnalytics>,
+        computation_offset: u64,
+        nonce: u128,
+    ) -> Result<()> {
+        let args = vec![Argument::PlaintextU128(nonce)];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::InitSurveyAnalytics,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        let survey_analytics = &mut ctx.accounts.survey_analytics;
+        survey_analytics.analytics_state = [[0u8; 32]; 4];
+        survey_analytics.nonce = 0;
+        survey_analytics.survey = ctx.accounts.survey.key();
+        survey_analytics.payer = ctx.accounts.payer.key();
+        survey_analytics.is_initialized = false;
+        survey_analytics.bump = ctx.bumps.survey_analytics;
+        ctx.accounts.survey.open_aggregate()?;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.survey_analytics.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "init_survey_analytics")]
+    pub fn init_survey_analytics_callback(
+        ctx: Context<InitSurveyAnalyticsCallback>,
+        output: ComputationOutputs<InitSurveyAnalyticsOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(InitSurveyAnalyticsOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
+        };
+
+        let computation_result = &mut ctx.accounts.computation_result;
+        computation_result.status = ComputationStatus::Completed;
+        computation_result.result_data = o.ciphertexts[0]; // SurveyAnalytics.total_responses
+        computation_result.nonce = o.nonce.to_le_bytes();
+        computation_result.completed_at = Clock::get()?.unix_timestamp;
+
+        let survey_analytics = &mut ctx.accounts.survey_analytics;
+        survey_analytics.analytics_state = o.ciphertexts;
+        survey_analytics.nonce = o.nonce;
+        survey_analytics.is_initialized = true;
+
+        emit!(SurveyAnalyticsUpdatedEvent {
+            survey: survey_analytics.survey,
+            analytics: o.ciphertexts,
+            nonce: o.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    // Creates the singleton config. Only the program's upgrade authority may
+    // call this, which closes the race for the admin role after deployment.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        treasury: Pubkey,
+        protocol_fee_lamports: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.pending_authority = None;
+        config.paused = false;
+        config.protocol_fee_lamports = protocol_fee_lamports;
+        config.treasury = treasury;
+        config.bump = ctx.bumps.config;
+
+        emit!(ConfigUpdatedEvent {
+            authority: config.authority,
+            pending_authority: config.pending_authority,
+            paused: config.paused,
+            protocol_fee_lamports,
+            treasury,
+        });
+        Ok(())
+    }
+
+    // Authority handover is two-step so a mistyped key cannot lock out the admin:
+    // the current authority proposes, and the proposed key has to accept.
+    pub fn transfer_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_authority = Some(new_authority);
+        emit_config_updated(config);
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.new_authority.key();
+        config.pending_authority = None;
+        emit_config_updated(config);
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+        emit_config_updated(config);
+        Ok(())
+    }
+
+    pub fn update_fees(
+        ctx: Context<UpdateConfig>,
+        protocol_fee_lamports: u64,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.protocol_fee_lamports = protocol_fee_lamports;
+        config.treasury = treasury;
+        emit_config_updated(config);
+        Ok(())
+    }
+
+    // Rent reclaim. Each close returns lamports to whoever paid for the account
+    // and may be called by that owner or by the config authority.
+
+    pub fn close_computation_result(ctx: Context<CloseComputationResult>) -> Result<()> {
+        require_owner_or_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.config,
+        )?;
+        let computation_result = &ctx.accounts.computation_result;
+        let finished = matches!(
+            computation_result.status,
+            ComputationStatus::Completed | ComputationStatus::Failed
+        );
+        let stale = Clock::get()?.unix_timestamp - computation_result.created_at
+            >= STALE_COMPUTATION_SECS;
+        require!(finished || stale, ErrorCode::ComputationInFlight);
+        Ok(())
+    }
+
+    pub fn close_response(ctx: Context<CloseResponse>) -> Result<()> {
+        require_owner_or_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.respondent.key(),
+            &ctx.accounts.config,
+        )?;
+        require!(
+            ctx.accounts.survey.status == SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+        // A computation that never called back is given up on after
+        // STALE_COMPUTATION_SECS, like in close_computation_result
+        let response = &ctx.accounts.response;
+        let in_flight =
+            response.status == ComputationStatus::Pending || response.reward_claim_pending;
+        let stale = Clock::get()?.unix_timestamp - response.queued_at >= STALE_COMPUTATION_SECS;
+        require!(!in_flight || stale, ErrorCode::ComputationInFlight);
+        // An unclaimed reward is forfeited and returned to the withdrawable balance.
+        // Failed responses released theirs in the callback already
+        if !response.reward_paid && response.status != ComputationStatus::Failed {
+            let reward_amount = response.reward_amount;
+            ctx.accounts.survey.release_reward(reward_amount);
+        }
+        let survey = &mut ctx.accounts.survey;
+        survey.response_accounts = survey.response_accounts.saturating_sub(1);
+        Ok(())
+    }
+
+    pub fn close_response_receipt(ctx: Context<CloseResponseReceipt>) -> Result<()> {
+        require_owner_or_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.respondent.key(),
+            &ctx.accounts.config,
+        )?;
+        let survey = &mut ctx.accounts.survey;
+        require!(
+            survey.status == SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+        // The submit_response callback writes to the receipt, so wait until
+        // the latest submission can no longer call back
+        let last_submitted_at = ctx.accounts.response_receipt.last_submitted_at;
+        require!(
+            Clock::get()?.unix_timestamp - last_submitted_at >= STALE_COMPUTATION_SECS,
+            ErrorCode::ComputationInFlight
+        );
+        survey.response_accounts = survey.response_accounts.saturating_sub(1);
+        Ok(())
+    }
+
+    // Closes a closed survey together with its empty reward vault. Responses,
+    // receipts and aggregate accounts must be closed first, while the survey
+    // account still exists to prove it is closed.
+    pub fn close_survey_account(ctx: Context<CloseSurveyAccount>) -> Result<()> {
+        let survey = &ctx.accounts.survey;
+        survey.require_owner_or_config_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.config,
+        )?;
+        require!(
+            survey.status == SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+        require!(survey.rewards_committed == 0, ErrorCode::ComputationInFlight);
+        require!(survey.aggregate_accounts == 0, ErrorCode::SurveyAggregatesOpen);
+        require!(survey.response_accounts == 0, ErrorCode::SurveyResponsesOpen);
+
+        if survey.reward_mint.is_some() {
+            let (Some(vault), Some(token_program)) = (
+                ctx.accounts.reward_vault.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return Err(ErrorCode::MissingRewardAccount.into());
+            };
+            require!(vault.amount == 0, ErrorCode::RewardVaultNotEmpty);
+
+            let survey_id = survey.survey_id.to_le_bytes();
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                b"survey",
+                survey.creator.as_ref(),
+                survey_id.as_ref(),
+                &[survey.bump],
+            ]];
+            token::close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: ctx.accounts.creator.to_account_info(),
+                    authority: survey.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+        Ok(())
+    }
+
+    // Aggregate accounts may be closed by a survey owner or the config authority
+    // once the survey is closed. The rent goes back to whoever initialized them.
+
+    pub fn close_survey_analytics(ctx: Context<CloseSurveyAnalytics>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts
+            .survey
+            .close_aggregate(&authority, &ctx.accounts.config)
+    }
+
+    pub fn close_choice_tally(ctx: Context<CloseChoiceTally>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts
+            .survey
+            .close_aggregate(&authority, &ctx.accounts.config)
+    }
+
+    pub fn close_rating_stats(ctx: Context<CloseRatingStats>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts
+            .survey
+            .close_aggregate(&authority, &ctx.accounts.config)
+    }
+
+    pub fn close_cross_tab(ctx: Context<CloseCrossTab>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts
+            .survey
+            .close_aggregate(&authority, &ctx.accounts.config)
+    }
+
+    // A quiz can be closed once its attempts and whitelists are, and nothing
+    // was queued against it for STALE_COMPUTATION_SECS, so no grading callback
+    // still needs to read it
+    pub fn close_quiz(ctx: Context<CloseQuiz>) -> Result<()> {
+        require_owner_or_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.config,
+        )?;
+        let quiz = &ctx.accounts.quiz;
+        require!(quiz.open_accounts == 0, ErrorCode::QuizAccountsOpen);
+        require!(
+            quiz.is_idle(Clock::get()?.unix_timestamp),
+            ErrorCode::ComputationInFlight
+        );
+        Ok(())
+    }
+
+    // Attempts and whitelists are written by the grading callback, so an attempt
+    // being graded, or any whitelist of a quiz graded recently, stays open until
+    // the grading can no longer call back
+    pub fn close_quiz_attempt(ctx: Context<CloseQuizAttempt>) -> Result<()> {
+        require_owner_or_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.participant.key(),
+            &ctx.accounts.config,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        let quiz = &mut ctx.accounts.quiz;
+        require!(
+            ctx.accounts.quiz_attempt.status != QuizAttemptStatus::Grading || quiz.is_idle(now),
+            ErrorCode::QuizGradingInFlight
+        );
+        quiz.open_accounts = quiz.open_accounts.saturating_sub(1);
+        Ok(())
+    }
+
+    // The config authority may only reclaim whitelists that no longer gate anything
+    pub fn close_whitelist(ctx: Context<CloseWhitelist>) -> Result<()> {
+        require_owner_or_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.wallet.key(),
+            &ctx.accounts.config,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        let quiz = &mut ctx.accounts.quiz;
+        require!(quiz.is_idle(now), ErrorCode::ComputationInFlight);
+        if ctx.accounts.authority.key() != ctx.accounts.wallet.key() {
+            require!(!ctx.accounts.whitelist.is_valid(now), ErrorCode::Unauthorized);
+        }
+        quiz.open_accounts = quiz.open_accounts.saturating_sub(1);
+        Ok(())
+    }
+
+    pub fn init_upload_answer_key_comp_def(ctx: Context<InitUploadAnswerKeyCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_grade_quiz_comp_def(ctx: Context<InitGradeQuizCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    // Creates a quiz and uploads its answer key, encrypted by the creator and
+    // re-encrypted to the MXE in the computation. Unused question slots must
+    // be encrypted with zero points.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_quiz(
+        ctx: Context<CreateQuiz>,
+        computation_offset: u64,
+        quiz_id: u64,
+        question_count: u8,
+        minimum_score: u64,
+        whitelist_validity: Option<i64>,
+        max_attempts: u16,
+        time_limit: Option<i64>,
+        answer_ciphertexts: [[u8; 32]; MAX_QUIZ_QUESTIONS],
+        points_ciphertexts: [[u8; 32]; MAX_QUIZ_QUESTIONS],
+        pub_key: [u8; 32],
+        nonce: u128,
+        mxe_nonce: u128,
+    ) -> Result<()> {
+        require!(
+            question_count > 0 && question_count as usize <= MAX_QUIZ_QUESTIONS,
+            ErrorCode::InvalidQuestionCount
+        );
+        require!(
+            !matches!(whitelist_validity, Some(validity) if validity <= 0),
+            ErrorCode::InvalidWhitelistValidity
+        );
+        require!(max_attempts > 0, ErrorCode::InvalidAttemptLimit);
+        require!(
+            !matches!(time_limit, Some(limit) if limit <= 0),
+            ErrorCode::InvalidAttemptLimit
+        );
+
+        let args = answer_key_args(
+            answer_ciphertexts,
+            points_ciphertexts,
+            pub_key,
+            nonce,
+            mxe_nonce,
+        );
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::UploadAnswerKey,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+
+        let quiz = &mut ctx.accounts.quiz;
+        quiz.answer_key = [[0u8; 32]; 2 * MAX_QUIZ_QUESTIONS];
+        quiz.answer_key_nonce = 0;
+        quiz.creator = ctx.accounts.payer.key();
+        quiz.quiz_id = quiz_id;
+        quiz.question_count = question_count;
+        quiz.minimum_score = minimum_score;
+        quiz.whitelist_validity = whitelist_validity;
+        quiz.max_attempts = max_attempts;
+        quiz.time_limit = time_limit;
+        quiz.key_uploaded = false;
+        quiz.key_upload_pending = true;
+        quiz.created_at = Clock::get()?.unix_timestamp;
+        quiz.last_queued_at = quiz.created_at;
+        quiz.open_accounts = 0;
+        quiz.bump = ctx.bumps.quiz;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.quiz.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "upload_answer_key")]
+    pub fn upload_answer_key_callback(
+        ctx: Context<UploadAnswerKeyCallback>,
+        output: ComputationOutputs<UploadAnswerKeyOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(UploadAnswerKeyOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                // The creator can queue the upload again with upload_answer_key
+                ctx.accounts.quiz.key_upload_pending = false;
+                return Ok(());
+            }
+        };
+
+        // The answer key itself is never copied into the result record
+        let computation_result = &mut ctx.accounts.computation_result;
+        computation_result.status = ComputationStatus::Completed;
+        computation_result.nonce = o.nonce.to_le_bytes();
+        computation_result.completed_at = Clock::get()?.unix_timestamp;
+
+        let quiz = &mut ctx.accounts.quiz;
+        quiz.answer_key = o.ciphertexts;
+        quiz.answer_key_nonce = o.nonce;
+        quiz.key_uploaded = true;
+        quiz.key_upload_pending = false;
+
+        emit!(AnswerKeyUploadedEvent { quiz: quiz.key() });
+        Ok(())
+    }
+
+    // Queues the answer key upload again after an aborted one. Only the
+    // creator may upload, and only while the quiz has no key and no upload
+    // in flight; the ciphertexts are laid out as in create_quiz.
+    #[allow(clippy::too_many_arguments)]
+    pub fn upload_answer_key(
+        ctx: Context<UploadAnswerKey>,
+        computation_offset: u64,
+        answer_ciphertexts: [[u8; 32]; MAX_QUIZ_QUESTIONS],
+        points_ciphertexts: [[u8; 32]; MAX_QUIZ_QUESTIONS],
+        pub_key: [u8; 32],
+        nonce: u128,
+        mxe_nonce: u128,
+    ) -> Result<()> {
+        let quiz = &mut ctx.accounts.quiz;
+        require!(
+            !quiz.key_uploaded && !quiz.key_upload_pending,
+            ErrorCode::AnswerKeyUploadInFlight
+        );
+        quiz.key_upload_pending = true;
+        quiz.last_queued_at = Clock::get()?.unix_timestamp;
+
+        let args = answer_key_args(
+            answer_ciphertexts,
+            points_ciphertexts,
+            pub_key,
+            nonce,
+            mxe_nonce,
+        );
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::UploadAnswerKey,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.quiz.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    // Starts a timed attempt. The start time comes from the Clock so the time
+    // limit can be checked when the answers are submitted.
+    pub fn start_quiz_attempt(ctx: Context<StartQuizAttempt>) -> Result<()> {
+        let quiz = &mut ctx.accounts.quiz;
+        require!(quiz.key_uploaded, ErrorCode::AnswerKeyNotUploaded);
+
+        let quiz_attempt = &mut ctx.accounts.quiz_attempt;
+        if quiz_attempt.status == QuizAttemptStatus::NotStarted {
+            quiz.open_account()?;
+            quiz_attempt.quiz = quiz.key();
+            quiz_attempt.participant = ctx.accounts.participant.key();
+            quiz_attempt.attempt_count = 0;
+            quiz_attempt.bump = ctx.bumps.quiz_attempt;
+        }
+        require!(
+            quiz_attempt.status != QuizAttemptStatus::Grading,
+            ErrorCode::QuizGradingInFlight
+        );
+        require!(
+            quiz_attempt.attempt_count < quiz.max_attempts,
+            ErrorCode::QuizAttemptsExhausted
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        quiz_attempt.attempt_count += 1;
+        quiz_attempt.started_at = now;
+        quiz_attempt.status = QuizAttemptStatus::InProgress;
+
+        emit!(QuizAttemptStartedEvent {
+            quiz: quiz.key(),
+            participant: quiz_attempt.participant,
+            attempt: quiz_attempt.attempt_count,
+            started_at: now,
+        });
+        Ok(())
+    }
+
+    // Submits encrypted choices for grading. The score comes back sealed to
+    // `pub_key`, so only the participant can read it; only whether it reached
+    // the quiz's minimum score is revealed, and a pass issues the whitelist.
+    pub fn submit_quiz(
+        ctx: Context<SubmitQuiz>,
+        computation_offset: u64,
+        choice_ciphertexts: [[u8; 32]; MAX_QUIZ_QUESTIONS],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let quiz = &mut ctx.accounts.quiz;
+        require!(quiz.key_uploaded, ErrorCode::AnswerKeyNotUploaded);
+        quiz.last_queued_at = now;
+
+        // Rejected here, before queueing, so a late or unstarted attempt never costs a computation
+        let quiz_attempt = &mut ctx.accounts.quiz_attempt;
+        require!(
+            quiz_attempt.status == QuizAttemptStatus::InProgress,
+            ErrorCode::QuizAttemptNotStarted
+        );
+        if let Some(time_limit) = quiz.time_limit {
+            require!(
+                now <= quiz_attempt.started_at.saturating_add(time_limit),
+                ErrorCode::QuizTimeLimitExceeded
+            );
+        }
+        quiz_attempt.encryption_key = pub_key;
+        quiz_attempt.score = [0u8; 32];
+        quiz_attempt.score_nonce = [0u8; 16];
+        quiz_attempt.status = QuizAttemptStatus::Grading;
+        quiz_attempt.graded_at = 0;
+
+        let whitelist = &mut ctx.accounts.whitelist;
+        if whitelist.wallet == Pubkey::default() {
+            quiz.open_account()?;
+            whitelist.quiz = quiz.key();
+            whitelist.wallet = ctx.accounts.payer.key();
+            whitelist.issued = false;
+            whitelist.issued_at = 0;
+            whitelist.expires_at = None;
+            whitelist.bump = ctx.bumps.whitelist;
+        }
+
+        let mut args = vec![Argument::ArcisPubkey(pub_key), Argument::PlaintextU128(nonce)];
+        args.extend(choice_ciphertexts.into_iter().map(Argument::EncryptedU8));
+        args.push(Argument::PlaintextU128(quiz.answer_key_nonce));
+        args.push(Argument::Account(
+            quiz.key(),
+            QUIZ_ANSWER_KEY_OFFSET,
+            32 * 2 * MAX_QUIZ_QUESTIONS as u32,
+        ));
+        args.push(Argument::PlaintextU64(quiz.minimum_score));
+
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::GradeQuiz,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.quiz_attempt.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.quiz.key(),
+                    is_writable: false,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.whitelist.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "grade_quiz")]
+    pub fn grade_quiz_callback(
+        ctx: Context<GradeQuizCallback>,
+        output: ComputationOutputs<GradeQuizOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(GradeQuizOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                ctx.accounts.quiz_attempt.status = QuizAttemptStatus::Failed;
+                return Ok(());
+            }
+        };
+
+        // Handle tuple return: (Enc<Shared, u64>, bool)
+        // field_0 is the score sealed to the participant, field_1 the revealed pass bit
+        let score = o.field_0;
+        let passed = o.field_1;
+
+        let now = Clock::get()?.unix_timestamp;
+        let computation_result = &mut ctx.accounts.computation_result;
+        computation_result.status = ComputationStatus::Completed;
+        computation_result.result_data = score.ciphertexts[0];
+        computation_result.nonce = score.nonce.to_le_bytes();
+        computation_result.completed_at = now;
+
+        let quiz_attempt = &mut ctx.accounts.quiz_attempt;
+        quiz_attempt.score = score.ciphertexts[0];
+        quiz_attempt.score_nonce = score.nonce.to_le_bytes();
+        quiz_attempt.status = QuizAttemptStatus::Graded;
+        quiz_attempt.graded_at = now;
+
+        emit!(QuizGradedEvent {
+            quiz: quiz_attempt.quiz,
+            participant: quiz_attempt.participant,
+            score: score.ciphertexts[0],
+            nonce: score.nonce.to_le_bytes(),
+            recipient: score.encryption_key,
+            passed,
+        });
+
+        // A failing attempt leaves an earlier whitelist untouched
+        if passed {
+            let whitelist = &mut ctx.accounts.whitelist;
+            whitelist.issued = true;
+            whitelist.issued_at = now;
+            whitelist.expires_at = ctx
+                .accounts
+                .quiz
+                .whitelist_validity
+                .map(|validity| now.saturating_add(validity));
+
+            emit!(WhitelistIssuedEvent {
+                quiz: whitelist.quiz,
+                wallet: whitelist.wallet,
+                expires_at: whitelist.expires_at,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn init_init_choice_tally_comp_def(ctx: Context<InitChoiceTallyCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_record_choice_comp_def(ctx: Context<InitRecordChoiceCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_reveal_choice_tally_comp_def(ctx: Context<InitRevealChoiceTallyCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    // Creates the encrypted histogram for a multiple-choice question
+    pub fn init_choice_tally(
+        ctx: Context<InitChoiceTally>,
+        computation_offset: u64,
+        question_index: u8,
+        option_count: u8,
+        nonce: u128,
+    ) -> Result<()> {
+        require!(
+            question_index < RECORD_MASK_BITS,
+            ErrorCode::InvalidQuestionIndex
+        );
+        require!(
+            option_count > 1 && option_count as usize <= MAX_CHOICE_OPTIONS,
+            ErrorCode::InvalidOptionCount
+        );
+        let survey = &ctx.accounts.survey;
+        survey.require_role(&ctx.accounts.payer.key(), &[SurveyRole::Owner])?;
+        require!(
+            survey.status != SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+
+        let args = vec![Argument::PlaintextU128(nonce)];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::InitChoiceTally,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        let choice_tally = &mut ctx.accounts.choice_tally;
+        choice_tally.counts = [[0u8; 32]; MAX_CHOICE_OPTIONS];
+        choice_tally.nonce = 0;
+        choice_tally.survey = ctx.accounts.survey.key();
+        choice_tally.payer = ctx.accounts.payer.key();
+        choice_tally.question_index = question_index;
+        choice_tally.option_count = option_count;
+        choice_tally.is_initialized = false;
+        choice_tally.bump = ctx.bumps.choice_tally;
+        ctx.accounts.survey.open_aggregate()?;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.choice_tally.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "init_choice_tally")]
+    pub fn init_choice_tally_callback(
+        ctx: Context<InitChoiceTallyCallback>,
+        output: ComputationOutputs<InitChoiceTallyOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(InitChoiceTallyOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
+        };
+
+        ctx.accounts
+            .computation_result
+            .record_success(None, o.nonce)?;
+
+        let choice_tally = &mut ctx.accounts.choice_tally;
+        choice_tally.counts = o.ciphertexts;
+        choice_tally.nonce = o.nonce;
+        choice_tally.is_initialized = true;
+        Ok(())
+    }
+
+    // Tallies the respondent's encrypted option index for one question. Each
+    // processed response may contribute once per question; a choice outside
+    // the question's options is rejected in MPC and may be recorded again.
+    // Questions bound to a cross-tab are recorded through record_cross_tab.
+    pub fn record_choice(
+        ctx: Context<RecordChoice>,
+        computation_offset: u64,
+        question_index: u8,
+        choice_ciphertext: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.survey.status == SurveyStatus::Open,
+            ErrorCode::SurveyNotOpen
+        );
+        require!(
+            !ctx.accounts.survey.in_cross_tab(question_index),
+            ErrorCode::QuestionInCrossTab
+        );
+        let response = &mut ctx.accounts.response;
+        require!(
+            response.status == ComputationStatus::Completed,
+            ErrorCode::ResponseNotCompleted
+        );
+        Response::claim_record(
+            &mut response.recorded_choices,
+            question_index,
+            ErrorCode::ChoiceAlreadyRecorded,
+        )?;
+
+        let choice_tally = &ctx.accounts.choice_tally;
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU8(choice_ciphertext),
+            Argument::PlaintextU128(choice_tally.nonce),
+            Argument::Account(choice_tally.key(), 8, 32 * MAX_CHOICE_OPTIONS as u32),
+            Argument::PlaintextU64(choice_tally.option_count as u64),
+        ];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::RecordChoice,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+        ctx.accounts.computation_result.response_id = Some(ctx.accounts.response.key().to_bytes());
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.choice_tally.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.response.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "record_choice")]
+    pub fn record_choice_callback(
+        ctx: Context<RecordChoiceCallback>,
+        output: ComputationOutputs<RecordChoiceOutput>,
+    ) -> Result<()> {
+        // Handle tuple return: (Enc<Mxe, [u64; MAX_CHOICE_OPTIONS]>, bool)
+        // field_1 reveals whether the choice was within the question's options
+        let (tally, accepted) = match output {
+            ComputationOutputs::Success(RecordChoiceOutput { field_0: o }) => {
+                (Some(o.field_0), o.field_1)
+            }
+            _ => (None, false),
+        };
+        let question_index = ctx.accounts.choice_tally.question_index;
+        settle_record(
+            &mut ctx.accounts.computation_result,
+            tally.as_ref().map(|tally| tally.nonce),
+            accepted,
+            &mut ctx.accounts.response.recorded_choices,
+            &[question_index],
+        )?;
+
+        if let Some(tally) = tally {
+            let choice_tally = &mut ctx.accounts.choice_tally;
+            choice_tally.counts = tally.ciphertexts;
+            choice_tally.nonce = tally.nonce;
+            if !accepted {
+                emit!(ChoiceRejectedEvent {
+                    response: ctx.accounts.response.key(),
+                    question_index,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Seals a differentially private copy of a question's histogram to
+    // `pub_key`, spending `epsilon_milli` of the survey's privacy budget. Owners
+    // and analysts may request it; until the survey's k-anonymity minimum is
+    // met the circuit returns u64::MAX in every bucket instead of the counts.
+    pub fn reveal_choice_tally(
+        ctx: Context<RevealChoiceTally>,
+        computation_offset: u64,
+        pub_key: [u8; 32],
+        nonce: u128,
+        epsilon_milli: u64,
+    ) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        survey.require_role(
+            &ctx.accounts.payer.key(),
+            &[SurveyRole::Owner, SurveyRole::Analyst],
+        )?;
+        let remaining = survey.spend_privacy(epsilon_milli)?;
+        emit!(PrivacyBudgetSpentEvent {
+            survey: survey.key(),
+            epsilon_milli,
+            remaining,
+        });
+
+        let choice_tally = &ctx.accounts.choice_tally;
+        let mut args = vec![
+            Argument::PlaintextU128(choice_tally.nonce),
+            Argument::Account(choice_tally.key(), 8, 32 * MAX_CHOICE_OPTIONS as u32),
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::PlaintextU64(survey.settings.min_reveal_responses),
+        ];
+        args.extend(
+            noise_thresholds(epsilon_milli, 1)?
+                .into_iter()
+                .map(Argument::PlaintextU64),
+        );
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::RevealChoiceTally,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.choice_tally.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_choice_tally")]
+    pub fn reveal_choice_tally_callback(
+        ctx: Context<RevealChoiceTallyCallback>,
+        output: ComputationOutputs<RevealChoiceTallyOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(RevealChoiceTallyOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
+        };
+
+        ctx.accounts
+            .computation_result
+            .record_success(Some(o.ciphertexts[0]), o.nonce)?;
+
+        let choice_tally = &ctx.accounts.choice_tally;
+        emit!(ChoiceTallyRevealedEvent {
+            survey: choice_tally.survey,
+            question_index: choice_tally.question_index,
+            counts: o.ciphertexts,
+            nonce: o.nonce.to_le_bytes(),
+            recipient: o.encryption_key,
+        });
+        Ok(())
+    }
+
+    pub fn init_init_rating_stats_comp_def(ctx: Context<InitRatingStatsCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_record_rating_comp_def(ctx: Context<InitRecordRatingCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_finalize_rating_stats_comp_def(ctx: Context<InitFinalizeRatingStatsCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    // Creates the encrypted accumulators for a rating question
+    pub fn init_rating_stats(
+        ctx: Context<InitRatingStats>,
+        computation_offset: u64,
+        question_index: u8,
+        nonce: u128,
+    ) -> Result<()> {
+        require!(
+            question_index < RECORD_MASK_BITS,
+            ErrorCode::InvalidQuestionIndex
+        );
+        let survey = &ctx.accounts.survey;
+        survey.require_role(&ctx.accounts.payer.key(), &[SurveyRole::Owner])?;
+        require!(
+            survey.status != SurveyStatus::Closed,
+            ErrorCode::InvalidSurveyStatus
+        );
+
+        let args = vec![Argument::PlaintextU128(nonce)];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::InitRatingStats,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        let rating_stats = &mut ctx.accounts.rating_stats;
+        rating_stats.stats = [[0u8; 32]; 3];
+        rating_stats.nonce = 0;
+        rating_stats.survey = ctx.accounts.survey.key();
+        rating_stats.payer = ctx.accounts.payer.key();
+        rating_stats.question_index = question_index;
+        rating_stats.is_initialized = false;
+        rating_stats.bump = ctx.bumps.rating_stats;
+        ctx.accounts.survey.open_aggregate()?;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.rating_stats.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "init_rating_stats")]
+    pub fn init_rating_stats_callback(
+        ctx: Context<InitRatingStatsCallback>,
+        output: ComputationOutputs<InitRatingStatsOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(InitRatingStatsOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
+        };
+
+        ctx.accounts
+            .computation_result
+            .record_success(None, o.nonce)?;
+
+        let rating_stats = &mut ctx.accounts.rating_stats;
+        rating_stats.stats = o.ciphertexts;
+        rating_stats.nonce = o.nonce;
+        rating_stats.is_initialized = true;
+        Ok(())
+    }
+
+    // Adds the respondent's encrypted rating to a question's accumulators. Each
+    // processed response may contribute once per question.
+    pub fn record_rating(
+        ctx: Context<RecordRating>,
+        computation_offset: u64,
+        question_index: u8,
+        rating_ciphertext: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.survey.status == SurveyStatus::Open,
+            ErrorCode::SurveyNotOpen
+        );
+        let response = &mut ctx.accounts.response;
+        require!(
+            response.status == ComputationStatus::Completed,
+            ErrorCode::ResponseNotCompleted
+        );
+        Response::claim_record(
+            &mut response.recorded_ratings,
+            question_index,
+            ErrorCode::RatingAlreadyRecorded,
+        )?;
+
+        let rating_stats = &ctx.accounts.rating_stats;
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU8(rating_ciphertext),
+            Argument::PlaintextU128(rating_stats.nonce),
+            Argument::Account(rating_stats.key(), 8, 32 * 3),
+        ];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::RecordRating,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+        ctx.accounts.computation_result.response_id = Some(ctx.accounts.response.key().to_bytes());
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.rating_stats.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.response.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "record_rating")]
+    pub fn record_rating_callback(
+        ctx: Context<RecordRatingCallback>,
+        output: ComputationOutputs<RecordRatingOutput>,
+    ) -> Result<()> {
+        let stats = match output {
+            ComputationOutputs::Success(RecordRatingOutput { field_0: o }) => Some(o),
+            _ => None,
+        };
+        // Out-of-range ratings are clamped in MPC, so every landed rating counts
+        let question_index = ctx.accounts.rating_stats.question_index;
+        settle_record(
+            &mut ctx.accounts.computation_result,
+            stats.as_ref().map(|stats| stats.nonce),
+            true,
+            &mut ctx.accounts.response.recorded_ratings,
+            &[question_index],
+        )?;
+
+        if let Some(stats) = stats {
+            let rating_stats = &mut ctx.accounts.rating_stats;
+            rating_stats.stats = stats.ciphertexts;
+            rating_stats.nonce = stats.nonce;
+        }
+        Ok(())
+    }
+
+    // Seals differentially private count, mean and variance of a rating
+    // question to `pub_key`, spending `epsilon_milli` of the survey's privacy
+    // budget split evenly across count, sum and sum of squares. Owners and
+    // analysts may request it; below the survey's k-anonymity minimum every
+    // field comes back as u64::MAX.
+    pub fn finalize_rating_stats(
+        ctx: Context<FinalizeRatingStats>,
+        computation_offset: u64,
+        pub_key: [u8; 32],
+        nonce: u128,
+        epsilon_milli: u64,
+    ) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        survey.require_role(
+            &ctx.accounts.payer.key(),
+            &[SurveyRole::Owner, SurveyRole::Analyst],
+        )?;
+        let remaining = survey.spend_privacy(epsilon_milli)?;
+        emit!(PrivacyBudgetSpentEvent {
+            survey: survey.key(),
+            epsilon_milli,
+            remaining,
+        });
+
+        let rating_stats = &ctx.accounts.rating_stats;
+        let share = epsilon_milli / 3;
+        require!(share > 0, ErrorCode::InvalidPrivacyBudget);
+        let mut args = vec![
+            Argument::PlaintextU128(rating_stats.nonce),
+            Argument::Account(rating_stats.key(), 8, 32 * 3),
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::PlaintextU64(survey.settings.min_reveal_responses),
+        ];
+        for sensitivity in [1, MAX_RATING, MAX_RATING * MAX_RATING] {
+            args.extend(
+                noise_thresholds(share, sensitivity)?
+                    .into_iter()
+                    .map(Argument::PlaintextU64),
+            );
+        }
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::FinalizeRatingStats,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.rating_stats.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "finalize_rating_stats")]
+    pub fn finalize_rating_stats_callback(
+        ctx: Context<FinalizeRatingStatsCallback>,
+        output: ComputationOutputs<FinalizeRatingStatsOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(FinalizeRatingStatsOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
+        };
+
+        // RatingSummary.mean
+        ctx.accounts
+            .computation_result
+            .record_success(Some(o.ciphertexts[1]), o.nonce)?;
+
+        let rating_stats = &ctx.accounts.rating_stats;
+        emit!(RatingSummaryEvent {
+            survey: rating_stats.survey,
+            question_index: rating_stats.question_index,
+            summary: o.ciphertexts,
+            nonce: o.nonce.to_le_bytes(),
+            recipient: o.encryption_key,
+        });
+        Ok(())
+    }
+
+    pub fn init_init_cross_tab_comp_def(ctx: Context<InitCrossTabCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_record_cross_tab_comp_def(ctx: Context<InitRecordCrossTabCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_reveal_cross_tab_comp_def(ctx: Context<InitRevealCrossTabCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    // Creates an encrypted contingency table between two survey questions.
+    // Both questions are bound to the table, so it has to exist before the
+    // survey first opens; their answers are then recorded only as pairs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_cross_tab(
+        ctx: Context<InitCrossTab>,
+        computation_offset: u64,
+        table_index: u8,
+        row_question: u8,
+        col_question: u8,
+        row_options: u8,
+        col_options: u8,
+        min_cell_size: u64,
+        nonce: u128,
+    ) -> Result<()> {
+        require!(
+            row_question < RECORD_MASK_BITS
+                && col_question < RECORD_MASK_BITS
+                && row_question != col_question,
+            ErrorCode::InvalidQuestionIndex
+        );
+        require!(
+            (2..=CROSS_TAB_OPTIONS).contains(&row_options)
+                && (2..=CROSS_TAB_OPTIONS).contains(&col_options),
+            ErrorCode::InvalidOptionCount
+        );
+        require!(min_cell_size > 0, ErrorCode::InvalidRevealThreshold);
+        let survey = &mut ctx.accounts.survey;
+        survey.require_role(&ctx.accounts.payer.key(), &[SurveyRole::Owner])?;
+        require!(
+            survey.status == SurveyStatus::Draft,
+            ErrorCode::InvalidSurveyStatus
+        );
+        survey.bind_cross_tab(row_question, col_question)?;
+
+        let args = vec![Argument::PlaintextU128(nonce)];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::InitCrossTab,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        let cross_tab = &mut ctx.accounts.cross_tab;
+        cross_tab.table = [[0u8; 32]; CROSS_TAB_CELLS];
+        cross_tab.nonce = 0;
+        cross_tab.survey = ctx.accounts.survey.key();
+        cross_tab.payer = ctx.accounts.payer.key();
+        cross_tab.table_index = table_index;
+        cross_tab.row_question = row_question;
+        cross_tab.col_question = col_question;
+        cross_tab.row_options = row_options;
+        cross_tab.col_options = col_options;
+        cross_tab.min_cell_size = min_cell_size;
+        cross_tab.is_initialized = false;
+        cross_tab.bump = ctx.bumps.cross_tab;
+        ctx.accounts.survey.open_aggregate()?;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.cross_tab.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.survey.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "init_cross_tab")]
+    pub fn init_cross_tab_callback(
+        ctx: Context<InitCrossTabCallback>,
+        output: ComputationOutputs<InitCrossTabOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(InitCrossTabOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                // Free the questions for another table; this one stays unusable
+                let cross_tab = &ctx.accounts.cross_tab;
+                ctx.accounts
+                    .survey
+                    .unbind_cross_tab(cross_tab.row_question, cross_tab.col_question);
+                return Ok(());
+            }
+        };
+
+        ctx.accounts
+            .computation_result
+            .record_success(None, o.nonce)?;
+
+        let cross_tab = &mut ctx.accounts.cross_tab;
+        cross_tab.table = o.ciphertexts;
+        cross_tab.nonce = o.nonce;
+        cross_tab.is_initialized = true;
+        Ok(())
+    }
+
+    // Records the respondent's encrypted answers to the table's row and column
+    // questions as one pair. Both question bits are claimed on the response, so
+    // each processed response answers them once, and are given back if MPC
+    // aborts or finds either answer outside its question's options.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_cross_tab(
+        ctx: Context<RecordCrossTab>,
+        computation_offset: u64,
+        table_index: u8,
+        row_ciphertext: [u8; 32],
+        col_ciphertext: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.survey.status == SurveyStatus::Open,
+            ErrorCode::SurveyNotOpen
+        );
+        let response = &mut ctx.accounts.response;
+        require!(
+            response.status == ComputationStatus::Completed,
+            ErrorCode::ResponseNotCompleted
+        );
+        let cross_tab = &ctx.accounts.cross_tab;
+        Response::claim_record(
+            &mut response.recorded_choices,
+            cross_tab.row_question,
+            ErrorCode::CrossTabAlreadyRecorded,
+        )?;
+        Response::claim_record(
+            &mut response.recorded_choices,
+            cross_tab.col_question,
+            ErrorCode::CrossTabAlreadyRecorded,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU8(row_ciphertext),
+            Argument::EncryptedU8(col_ciphertext),
+            Argument::PlaintextU128(cross_tab.nonce),
+            Argument::Account(cross_tab.key(), 8, 32 * CROSS_TAB_CELLS as u32),
+            Argument::PlaintextU64(cross_tab.row_options as u64),
+            Argument::PlaintextU64(cross_tab.col_options as u64),
+        ];
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::RecordCrossTab,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+        ctx.accounts.computation_result.response_id = Some(ctx.accounts.response.key().to_bytes());
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.cross_tab.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.response.key(),
+                    is_writable: true,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "record_cross_tab")]
+    pub fn record_cross_tab_callback(
+        ctx: Context<RecordCrossTabCallback>,
+        output: ComputationOutputs<RecordCrossTabOutput>,
+    ) -> Result<()> {
+        // Handle tuple return: (Enc<Mxe, table>, bool)
+        // field_1 reveals whether both answers were within their options
+        let (table, accepted) = match output {
+            ComputationOutputs::Success(RecordCrossTabOutput { field_0: o }) => {
+                (Some(o.field_0), o.field_1)
+            }
+            _ => (None, false),
+        };
+        let cross_tab = &ctx.accounts.cross_tab;
+        settle_record(
+            &mut ctx.accounts.computation_result,
+            table.as_ref().map(|table| table.nonce),
+            accepted,
+            &mut ctx.accounts.response.recorded_choices,
+            &[cross_tab.row_question, cross_tab.col_question],
+        )?;
+
+        if let Some(table) = table {
+            let cross_tab = &mut ctx.accounts.cross_tab;
+            cross_tab.table = table.ciphertexts;
+            cross_tab.nonce = table.nonce;
+            if !accepted {
+                emit!(CrossTabRejectedEvent {
+                    response: ctx.accounts.response.key(),
+                    table_index: cross_tab.table_index,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Seals a differentially private copy of the table to `pub_key`, spending
+    // `epsilon_milli` of the survey's privacy budget. Cells whose noised count
+    // is below the table's minimum size, and the whole table below the survey's
+    // k-anonymity minimum, come back as u64::MAX. Owners and analysts may
+    // request it.
+    pub fn reveal_cross_tab(
+        ctx: Context<RevealCrossTab>,
+        computation_offset: u64,
+        pub_key: [u8; 32],
+        nonce: u128,
+        epsilon_milli: u64,
+    ) -> Result<()> {
+        let survey = &mut ctx.accounts.survey;
+        survey.require_role(
+            &ctx.accounts.payer.key(),
+            &[SurveyRole::Owner, SurveyRole::Analyst],
+        )?;
+        let remaining = survey.spend_privacy(epsilon_milli)?;
+        emit!(PrivacyBudgetSpentEvent {
+            survey: survey.key(),
+            epsilon_milli,
+            remaining,
+        });
+
+        let cross_tab = &ctx.accounts.cross_tab;
+        let mut args = vec![
+            Argument::PlaintextU128(cross_tab.nonce),
+            Argument::Account(cross_tab.key(), 8, 32 * CROSS_TAB_CELLS as u32),
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::PlaintextU64(survey.settings.min_reveal_responses),
+            Argument::PlaintextU64(cross_tab.min_cell_size),
+        ];
+        args.extend(
+            noise_thresholds(epsilon_milli, 1)?
+                .into_iter()
+                .map(Argument::PlaintextU64),
+        );
+        ctx.accounts.computation_result.record_queued(
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+            ComputationType::RevealCrossTab,
+            ctx.accounts.payer.key(),
+            ctx.bumps.computation_result,
+        )?;
+        ctx.accounts.computation_result.survey_id = Some(ctx.accounts.survey.key().to_bytes());
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_result.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.cross_tab.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_cross_tab")]
+    pub fn reveal_cross_tab_callback(
+        ctx: Context<RevealCrossTabCallback>,
+        output: ComputationOutputs<RevealCrossTabOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(RevealCrossTabOutput { field_0: o }) => o,
+            _ => {
+                ctx.accounts
+                    .computation_result
+                    .record_failure(FailureReason::Aborted)?;
+                return Ok(());
+            }
+        };
+
+        ctx.accounts
+            .computation_result
+            .record_success(Some(o.ciphertexts[0]), o.nonce)?;
+
+        let cross_tab = &ctx.accounts.cross_tab;
+        emit!(CrossTabRevealedEvent {
+            survey: cross_tab.survey,
+            table_index: cross_tab.table_index,
+            table: o.ciphertexts,
+            nonce: o.nonce.to_le_bytes(),
+            recipient: o.encryption_key,
+        });
+        Ok(())
+    }
+}
+
+// Arguments of the upload_answer_key circuit, shared by create_quiz and
+// upload_answer_key
+fn answer_key_args(
+    answer_ciphertexts: [[u8; 32]; MAX_QUIZ_QUESTIONS],
+    points_ciphertexts: [[u8; 32]; MAX_QUIZ_QUESTIONS],
+    pub_key: [u8; 32],
+    nonce: u128,
+    mxe_nonce: u128,
+) -> Vec<Argument> {
+    let mut args = vec![Argument::ArcisPubkey(pub_key), Argument::PlaintextU128(nonce)];
+    args.extend(answer_ciphertexts.into_iter().map(Argument::EncryptedU8));
+    args.extend(points_ciphertexts.into_iter().map(Argument::EncryptedU8));
+    args.push(Argument::PlaintextU128(mxe_nonce));
+    args
+}
+
+fn require_owner_or_authority(signer: &Pubkey, owner: &Pubkey, config: &Config) -> Result<()> {
+    require!(
+        signer == owner || *signer == config.authority,
+        ErrorCode::Unauthorized
+    );
+    Ok(())
+}
+
+fn emit_config_updated(config: &Config) {
+    emit!(ConfigUpdatedEvent {
+        authority: config.authority,
+        pending_authority: config.pending_authority,
+        paused: config.paused,
+        protocol_fee_lamports: config.protocol_fee_lamports,
+        treasury: config.treasury,
+    });
+}
+
+// Pays out of the survey's reward vault, signing as the survey PDA.
+fn transfer_from_vault<'info>(
+    survey: &Account<'info, Survey>,
+    vault: &Account<'info, TokenAccount>,
+    destination: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let survey_id = survey.survey_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"survey",
+        survey.creator.as_ref(),
+        survey_id.as_ref(),
+        &[survey.bump],
+    ]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: destination.to_account_info(),
+                authority: survey.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+// Fractional bits of the fixed-point epsilon / sensitivity in noise_thresholds
+const NOISE_SCALE_FRAC_BITS: u32 = 32;
+
+// exp(-2^(b - NOISE_SCALE_FRAC_BITS)) in Q64 for each bit b of a fixed-point
+// scale below 64. Larger scales give an alpha under 2^-64, which rounds to zero.
+const EXP_NEG_POW2_Q64: [u128; 38] = [
+    0xffffffff00000000, 0xfffffffe00000002, 0xfffffffc00000008,
+    0xfffffff800000020, 0xfffffff000000080, 0xffffffe000000200,
+    0xffffffc000000800, 0xffffff8000002000, 0xffffff0000008000,
+    0xfffffe0000020000, 0xfffffc0000080000, 0xfffff80000200000,
+    0xfffff000007ffffd, 0xffffe00001ffffeb, 0xffffc00007ffff55,
+    0xffff80001ffffaab, 0xffff00007fffd555, 0xfffe0001fffeaaab,
+    0xfffc0007fff55560, 0xfff8001fffaaab55, 0xfff0007ffd556000,
+    0xffe001ffeaab5551, 0xffc007ff555fff77, 0xff801ffaab554446,
+    0xff007fd55ffdde39, 0xfe01feab551127cc, 0xfc07f55ff77d2494,
+    0xf81fab5445aebc8a, 0xf07d5fde38151e73, 0xe1eb51276c110c3c,
+    0xc75f7cf564105743, 0x9b4597e37cb04ff4, 0x5e2d58d8b3bcdf1b,
+    0x22a555477f039740, 0x04b0556e084f3d1e, 0x0015fc21041027ad,
+    0x000001e355bbaee8, 0x000000000003908d,
+];
+
+const ONE_Q64: u128 = 1 << 64;
+
+// Product of two Q64 fractions below one
+fn mul_q64(a: u128, b: u128) -> u128 {
+    (a * b) >> 64
+}
+
+// Per-digit Bernoulli thresholds, scaled to 2^NOISE_BITS, for two-sided
+// geometric noise with alpha = exp(-epsilon / sensitivity). Digit k of a
+// geometric sample is 1 with probability alpha^(2^k) / (1 + alpha^(2^k)).
+// Computed in integer fixed point: alpha is the product of the table entries
+// for the set bits of epsilon / sensitivity. The circuit keeps NOISE_DIGITS
+// digits, so ratios whose noise reaches 2^NOISE_DIGITS with probability
+// 2^-NOISE_BITS or more are rejected instead of silently wrapping.
+fn noise_thresholds(epsilon_milli: u64, sensitivity: u64) -> Result<[u64; NOISE_DIGITS]> {
+    let scale = ((epsilon_milli as u128) << NOISE_SCALE_FRAC_BITS) / (1000 * sensitivity as u128);
+    require!(scale > 0, ErrorCode::NoiseScaleTooLarge);
+
+    let mut power = if scale >> EXP_NEG_POW2_Q64.len() == 0 {
+        EXP_NEG_POW2_Q64
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| (scale >> bit) & 1 == 1)
+            .fold(ONE_Q64, |alpha, (_, factor)| mul_q64(alpha, *factor))
+    } else {
+        0
+    };
+    let mut thresholds = [0u64; NOISE_DIGITS];
+    for threshold in thresholds.iter_mut() {
+        *threshold = ((power << NOISE_BITS) / (ONE_Q64 + power)) as u64;
+        power = mul_q64(power, power);
+    }
+    // power is now alpha^(2^NOISE_DIGITS), the chance a sample needs more digits
+    require!(power >> (64 - NOISE_BITS) == 0, ErrorCode::NoiseScaleTooLarge);
+    Ok(thresholds)
+}
+
+fn set_survey_status(survey: &mut Account<Survey>, status: SurveyStatus) -> Result<()> {
+    survey.transition(status)?;
+
+    emit!(SurveyStatusChangedEvent {
+        survey: survey.key(),
+        status,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[queue_computation_accounts("add_together", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct AddTogether<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("add_together", payer)]
+#[derive(Accounts)]
+pub struct AddTogetherCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Sysvar account provided by runtime; verified by address constraint above.
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+}
+
+#[init_computation_definition_accounts("add_together", payer)]
+#[derive(Accounts)]
+pub struct InitAddTogetherCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("submit_response", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct SubmitResponse<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + size_of::<ResponseReceipt>(),
+        seeds = [b"response_receipt", survey.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub response_receipt: Box<Account<'info, ResponseReceipt>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<Response>(),
+        seeds = [
+            b"response",
+            survey.key().as_ref(),
+            payer.key().as_ref(),
+            response_receipt.response_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(
+        seeds = [b"survey_analytics", survey.key().as_ref()],
+        bump = survey_analytics.bump,
+        constraint = survey_analytics.is_initialized @ ErrorCode::AnalyticsNotInitialized
+    )]
+    pub survey_analytics: Box<Account<'info, SurveyAnalytics>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_RESPONSE))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    // Only required when the survey has a participation gate
+    pub gate_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub gate_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+    pub gate_whitelist: Option<Box<Account<'info, Whitelist>>>,
+    // Only required when the survey pays rewards
+    #[account(seeds = [b"reward_vault", survey.key().as_ref()], bump)]
+    pub reward_vault: Option<Box<Account<'info, TokenAccount>>>,
+    pub respondent_reward_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[callback_accounts("submit_response", payer)]
+#[derive(Accounts)]
+pub struct SubmitResponseCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_RESPONSE))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Sysvar account provided by runtime; verified by address constraint above.
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(
+        mut,
+        seeds = [b"survey_analytics", survey_analytics.survey.as_ref()],
+        bump = survey_analytics.bump
+    )]
+    pub survey_analytics: Box<Account<'info, SurveyAnalytics>>,
+    #[account(
+        mut,
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        seeds = [b"response_receipt", response.survey.as_ref(), response.respondent.as_ref()],
+        bump = response_receipt.bump
+    )]
+    pub response_receipt: Box<Account<'info, ResponseReceipt>>,
+}
+
+#[init_computation_definition_accounts("submit_response", payer)]
+#[derive(Accounts)]
+pub struct InitSubmitResponseCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: This account will be created by the Arcium program with correct seeds and type via CPI.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct SumEvent {
+    pub sum: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct ComputationFailedEvent {
+    pub computation_offset: u64,
+    pub computation_type: ComputationType,
+    pub reason: FailureReason,
+}
+
+#[event]
+pub struct ResponseEvent {
+    pub response: [[u8; 32]; 4],
+    pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+}
+
+#[event]
+pub struct LoanEligibilityEvent {
+    pub response_account: Pubkey,
+    pub eligibility: [[u8; 32]; 3],
+    pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The computation was aborted")]
+    AbortedComputation,
+    #[msg("Cluster not set")]
+    ClusterNotSet,
+    #[msg("Signer is not allowed to manage this survey")]
+    Unauthorized,
+    #[msg("Survey status does not allow this transition")]
+    InvalidSurveyStatus,
+    #[msg("Survey is not open for responses")]
+    SurveyNotOpen,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Survey analytics have not been initialized")]
+    AnalyticsNotInitialized,
+    #[msg("Survey window or response cap is invalid")]
+    InvalidSurveyWindow,
+    #[msg("Survey has not started accepting responses yet")]
+    SurveyNotStarted,
+    #[msg("Survey response window has ended")]
+    SurveyEnded,
+    #[msg("Survey has reached its maximum number of responses")]
+    MaxResponsesReached,
+    #[msg("Per-wallet response limit must be at least one")]
+    InvalidResponseLimit,
+    #[msg("Wallet has reached its response limit for this survey")]
+    ResponseLimitReached,
+    #[msg("Respondent is not on the survey allowlist")]
+    NotOnAllowlist,
+    #[msg("Survey is gated and the gate accounts were not provided")]
+    MissingGateAccount,
+    #[msg("Respondent does not meet the survey's participation gate")]
+    ParticipationGateNotMet,
+    #[msg("Survey pays rewards and the reward accounts were not provided")]
+    MissingRewardAccount,
+    #[msg("Reward token account does not match the survey's reward vault")]
+    InvalidRewardAccount,
+    #[msg("Reward vault does not hold enough unreserved tokens")]
+    InsufficientRewardFunds,
+    #[msg("Survey does not use quality-weighted rewards")]
+    InvalidRewardMode,
+    #[msg("Response has not been processed successfully")]
+    ResponseNotCompleted,
+    #[msg("Reward for this response was already claimed or is being computed")]
+    RewardAlreadyClaimed,
+    #[msg("Account is still referenced by an in-flight computation or reward")]
+    ComputationInFlight,
+    #[msg("Reward vault must be empty before the survey is closed")]
+    RewardVaultNotEmpty,
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Treasury does not match the program config")]
+    InvalidTreasury,
+    #[msg("Quiz question count must be between 1 and MAX_QUIZ_QUESTIONS")]
+    InvalidQuestionCount,
+    #[msg("Quiz answer key has not been uploaded yet")]
+    AnswerKeyNotUploaded,
+    #[msg("Previous quiz attempt is still being graded")]
+    QuizGradingInFlight,
+    #[msg("Whitelist validity must be positive")]
+    InvalidWhitelistValidity,
+    #[msg("Quiz attempt and time limits must be positive")]
+    InvalidAttemptLimit,
+    #[msg("No quiz attempts left for this wallet")]
+    QuizAttemptsExhausted,
+    #[msg("Quiz attempt has not been started")]
+    QuizAttemptNotStarted,
+    #[msg("Quiz attempt was submitted after the time limit")]
+    QuizTimeLimitExceeded,
+    #[msg("Wallet already holds a role on this survey")]
+    AlreadySurveyMember,
+    #[msg("Survey member list is full")]
+    TooManySurveyMembers,
+    #[msg("Wallet holds no removable role on this survey")]
+    NotSurveyMember,
+    #[msg("Survey extension may only push back the end or raise the cap")]
+    InvalidSurveyExtension,
+    #[msg("Question index is out of range")]
+    InvalidQuestionIndex,
+    #[msg("Multiple-choice questions need between 2 and MAX_CHOICE_OPTIONS options")]
+    InvalidOptionCount,
+    #[msg("This response's choice for the question was already tallied")]
+    ChoiceAlreadyRecorded,
+    #[msg("Choice tally has not been initialized yet")]
+    ChoiceTallyNotInitialized,
+    #[msg("This response's rating for the question was already recorded")]
+    RatingAlreadyRecorded,
+    #[msg("Rating statistics have not been initialized yet")]
+    RatingStatsNotInitialized,
+    #[msg("Minimum responses before reveal must be at least 1")]
+    InvalidRevealThreshold,
+    #[msg("Privacy budget and per-release epsilon must be positive")]
+    InvalidPrivacyBudget,
+    #[msg("Survey privacy budget is exhausted")]
+    PrivacyBudgetExhausted,
+    #[msg("This response already answered one of the cross-tabulated questions")]
+    CrossTabAlreadyRecorded,
+    #[msg("Cross-tabulation has not been initialized yet")]
+    CrossTabNotInitialized,
+    #[msg("Reward for this response is not payable yet")]
+    RewardNotSettled,
+    #[msg("Close the survey's aggregate accounts first")]
+    SurveyAggregatesOpen,
+    #[msg("Close the quiz's attempts and whitelists first")]
+    QuizAccountsOpen,
+    #[msg("Computation has no completed result")]
+    ComputationNotCompleted,
+    #[msg("Epsilon is too small for the sensitivity; noise would overflow")]
+    NoiseScaleTooLarge,
+    #[msg("Question already belongs to a cross-tabulation")]
+    QuestionInCrossTab,
+    #[msg("Close the survey's responses and receipts first")]
+    SurveyResponsesOpen,
+    #[msg("The answer key is uploaded or an upload is in flight")]
+    AnswerKeyUploadInFlight,
+}
+
+#[queue_computation_accounts("create_survey", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, survey_id: u64)]
+pub struct CreateSurvey<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = config.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<Survey>(),
+        seeds = [b"survey", payer.key().as_ref(), survey_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_SURVEY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("create_survey", payer)]
+#[derive(Accounts)]
+pub struct CreateSurveyCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_SURVEY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+}
+
+#[init_computation_definition_accounts("create_survey", payer)]
+#[derive(Accounts)]
+pub struct InitCreateSurveyCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+
+#[event]
+pub struct SurveyCreatedEvent {
+    pub survey: Pubkey,
+    pub metrics: [[u8; 32]; 6],
+    pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+}
+
+#[event]
+pub struct SurveyAnalysisEvent {
+    pub survey: Pubkey,
+    pub analysis: [[u8; 32]; 4],
+    pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+}
+
+#[derive(Accounts)]
+pub struct UpdateSurveyStatus<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+}
+
+// Pausing and closing only stop activity, so unlike UpdateSurveyStatus they
+// stay available while the program is paused.
+#[derive(Accounts)]
+pub struct StopSurvey<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+}
+
+#[event]
+pub struct SurveyStatusChangedEvent {
+    pub survey: Pubkey,
+    pub status: SurveyStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SurveyExtendedEvent {
+    pub survey: Pubkey,
+    pub ends_at: Option<i64>,
+    pub max_responses: Option<u64>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSurveyMembers<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+}
+
+#[event]
+pub struct SurveyMemberAddedEvent {
+    pub survey: Pubkey,
+    pub member: Pubkey,
+    pub role: SurveyRole,
+}
+
+#[event]
+pub struct SurveyMemberRemovedEvent {
+    pub survey: Pubkey,
+    pub member: Pubkey,
+    pub role: SurveyRole,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSurveyAllowlist<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+}
+
+#[event]
+pub struct AllowlistRootUpdatedEvent {
+    pub survey: Pubkey,
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+#[derive(Accounts)]
+pub struct RequestResultReceipt<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+}
+
+#[event]
+pub struct ResultReceiptEvent {
+    pub survey: Pubkey,
+    pub computation_offset: u64,
+    pub computation_type: ComputationType,
+    pub result_hash: [u8; 32],         // ComputationResult::receipt_hash
+    pub completed_at: i64,
+    pub requested_by: Pubkey,
+}
+
+#[queue_computation_accounts("init_survey_analytics", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct InitSurveyAnalytics<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump,
+        constraint = survey.role_of(&payer.key()) == Some(SurveyRole::Owner) @ ErrorCode::Unauthorized
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<SurveyAnalytics>(),
+        seeds = [b"survey_analytics", survey.key().as_ref()],
+        bump
+    )]
+    pub survey_analytics: Box<Account<'info, SurveyAnalytics>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_SURVEY_ANALYTICS))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("init_survey_analytics", payer)]
+#[derive(Accounts)]
+pub struct InitSurveyAnalyticsCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_SURVEY_ANALYTICS))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"survey_analytics", survey_analytics.survey.as_ref()],
+        bump = survey_analytics.bump
+    )]
+    pub survey_analytics: Box<Account<'info, SurveyAnalytics>>,
+}
+
+#[init_computation_definition_accounts("init_survey_analytics", payer)]
+#[derive(Accounts)]
+pub struct InitSurveyAnalyticsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct SurveyAnalyticsUpdatedEvent {
+    pub survey: Pubkey,
+    pub analytics: [[u8; 32]; 4],
+    pub nonce: [u8; 16],
+}
+
+#[derive(Accounts)]
+pub struct FundRewardVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump,
+        constraint = survey.role_of(&authority.key()) == Some(SurveyRole::Owner) @ ErrorCode::Unauthorized
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"reward_vault", survey.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = survey
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = authority
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRewardVault<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump,
+        constraint = survey.role_of(&authority.key()) == Some(SurveyRole::Owner) @ ErrorCode::Unauthorized
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(mut, seeds = [b"reward_vault", survey.key().as_ref()], bump)]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, token::mint = reward_vault.mint)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct RewardVaultFundedEvent {
+    pub survey: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub reward_per_response: u64,
+}
+
+#[event]
+pub struct RewardPaidEvent {
+    pub survey: Pubkey,
+    pub respondent: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardSettledEvent {
+    pub survey: Pubkey,
+    pub respondent: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardVaultWithdrawnEvent {
+    pub survey: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct PayResponseReward<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(mut, seeds = [b"reward_vault", survey.key().as_ref()], bump)]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = response.reward_account @ ErrorCode::InvalidRewardAccount)]
+    pub respondent_reward_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[queue_computation_accounts("compute_reward", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ClaimResponseReward<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_COMPUTE_REWARD))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("compute_reward", payer)]
+#[derive(Accounts)]
+pub struct ComputeRewardCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_COMPUTE_REWARD))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(
+        mut,
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+}
+
+#[init_computation_definition_accounts("compute_reward", payer)]
+#[derive(Accounts)]
+pub struct InitComputeRewardCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseComputationResult<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized,
+        close = creator,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseResponse<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub respondent: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        has_one = respondent @ ErrorCode::Unauthorized,
+        close = respondent,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseResponseReceipt<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub respondent: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = response_receipt.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        has_one = respondent @ ErrorCode::Unauthorized,
+        close = respondent,
+        seeds = [
+            b"response_receipt",
+            response_receipt.survey.as_ref(),
+            response_receipt.respondent.as_ref()
+        ],
+        bump = response_receipt.bump
+    )]
+    pub response_receipt: Box<Account<'info, ResponseReceipt>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSurveyAccount<'info> {
+    pub authority: Signer<'info>,
+    // The creator paid for the survey, so the rent goes back to them
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized,
+        close = creator,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    // Required if the survey ever had a reward vault
+    #[account(mut, seeds = [b"reward_vault", survey.key().as_ref()], bump)]
+    pub reward_vault: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Option<Program<'info, Token>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSurveyAnalytics<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = survey_analytics.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        has_one = payer @ ErrorCode::Unauthorized,
+        close = payer,
+        seeds = [b"survey_analytics", survey_analytics.survey.as_ref()],
+        bump = survey_analytics.bump
+    )]
+    pub survey_analytics: Box<Account<'info, SurveyAnalytics>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChoiceTally<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = choice_tally.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        has_one = payer @ ErrorCode::Unauthorized,
+        close = payer,
+        seeds = [b"choice_tally", choice_tally.survey.as_ref(), &[choice_tally.question_index]],
+        bump = choice_tally.bump
+    )]
+    pub choice_tally: Box<Account<'info, ChoiceTally>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRatingStats<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = rating_stats.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        has_one = payer @ ErrorCode::Unauthorized,
+        close = payer,
+        seeds = [b"rating_stats", rating_stats.survey.as_ref(), &[rating_stats.question_index]],
+        bump = rating_stats.bump
+    )]
+    pub rating_stats: Box<Account<'info, RatingStats>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCrossTab<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = cross_tab.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        has_one = payer @ ErrorCode::Unauthorized,
+        close = payer,
+        seeds = [b"cross_tab", cross_tab.survey.as_ref(), &[cross_tab.table_index]],
+        bump = cross_tab.bump
+    )]
+    pub cross_tab: Box<Account<'info, CrossTab>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + size_of::<Config>(),
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_authority == Some(new_authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[event]
+pub struct ConfigUpdatedEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub paused: bool,
+    pub protocol_fee_lamports: u64,
+    pub treasury: Pubkey,
+}
+
+#[queue_computation_accounts("upload_answer_key", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, quiz_id: u64)]
+pub struct CreateQuiz<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<Quiz>(),
+        seeds = [b"quiz", payer.key().as_ref(), quiz_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_UPLOAD_ANSWER_KEY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("upload_answer_key", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct UploadAnswerKey<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        constraint = quiz.creator == payer.key() @ ErrorCode::Unauthorized,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_UPLOAD_ANSWER_KEY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("upload_answer_key", payer)]
+#[derive(Accounts)]
+pub struct UploadAnswerKeyCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_UPLOAD_ANSWER_KEY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+}
+
+#[init_computation_definition_accounts("upload_answer_key", payer)]
+#[derive(Accounts)]
+pub struct InitUploadAnswerKeyCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StartQuizAttempt<'info> {
+    #[account(mut)]
+    pub participant: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + size_of::<QuizAttempt>(),
+        seeds = [b"quiz_attempt", quiz.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub quiz_attempt: Box<Account<'info, QuizAttempt>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("grade_quiz", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct SubmitQuiz<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(
+        mut,
+        seeds = [b"quiz_attempt", quiz.key().as_ref(), payer.key().as_ref()],
+        bump = quiz_attempt.bump
+    )]
+    pub quiz_attempt: Box<Account<'info, QuizAttempt>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + size_of::<Whitelist>(),
+        seeds = [b"whitelist", quiz.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub whitelist: Box<Account<'info, Whitelist>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_GRADE_QUIZ))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("grade_quiz", payer)]
+#[derive(Accounts)]
+pub struct GradeQuizCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_GRADE_QUIZ))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"quiz_attempt", quiz_attempt.quiz.as_ref(), quiz_attempt.participant.as_ref()],
+        bump = quiz_attempt.bump
+    )]
+    pub quiz_attempt: Box<Account<'info, QuizAttempt>>,
+    #[account(
+        address = quiz_attempt.quiz,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(
+        mut,
+        seeds = [b"whitelist", quiz_attempt.quiz.as_ref(), quiz_attempt.participant.as_ref()],
+        bump = whitelist.bump
+    )]
+    pub whitelist: Box<Account<'info, Whitelist>>,
+}
+
+#[init_computation_definition_accounts("grade_quiz", payer)]
+#[derive(Accounts)]
+pub struct InitGradeQuizCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseQuiz<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized,
+        close = creator,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseQuizAttempt<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub participant: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = quiz_attempt.quiz,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(
+        mut,
+        has_one = participant @ ErrorCode::Unauthorized,
+        close = participant,
+        seeds = [b"quiz_attempt", quiz_attempt.quiz.as_ref(), quiz_attempt.participant.as_ref()],
+        bump = quiz_attempt.bump
+    )]
+    pub quiz_attempt: Box<Account<'info, QuizAttempt>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseWhitelist<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub wallet: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = whitelist.quiz,
+        seeds = [b"quiz", quiz.creator.as_ref(), quiz.quiz_id.to_le_bytes().as_ref()],
+        bump = quiz.bump
+    )]
+    pub quiz: Box<Account<'info, Quiz>>,
+    #[account(
+        mut,
+        has_one = wallet @ ErrorCode::Unauthorized,
+        close = wallet,
+        seeds = [b"whitelist", whitelist.quiz.as_ref(), whitelist.wallet.as_ref()],
+        bump = whitelist.bump
+    )]
+    pub whitelist: Box<Account<'info, Whitelist>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[event]
+pub struct AnswerKeyUploadedEvent {
+    pub quiz: Pubkey,
+}
+
+#[event]
+pub struct QuizAttemptStartedEvent {
+    pub quiz: Pubkey,
+    pub participant: Pubkey,
+    pub attempt: u16,
+    pub started_at: i64,
+}
+
+#[event]
+pub struct QuizGradedEvent {
+    pub quiz: Pubkey,
+    pub participant: Pubkey,
+    pub score: [u8; 32],
+    pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+    pub passed: bool,
+}
+
+#[event]
+pub struct WhitelistIssuedEvent {
+    pub quiz: Pubkey,
+    pub wallet: Pubkey,
+    pub expires_at: Option<i64>,
+}
+
+#[queue_computation_accounts("init_choice_tally", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, question_index: u8)]
+pub struct InitChoiceTally<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ChoiceTally>(),
+        seeds = [b"choice_tally", survey.key().as_ref(), &[question_index]],
+        bump
+    )]
+    pub choice_tally: Box<Account<'info, ChoiceTally>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_CHOICE_TALLY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("init_choice_tally", payer)]
+#[derive(Accounts)]
+pub struct InitChoiceTallyCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_CHOICE_TALLY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"choice_tally", choice_tally.survey.as_ref(), &[choice_tally.question_index]],
+        bump = choice_tally.bump
+    )]
+    pub choice_tally: Box<Account<'info, ChoiceTally>>,
+}
+
+#[init_computation_definition_accounts("init_choice_tally", payer)]
+#[derive(Accounts)]
+pub struct InitChoiceTallyCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("record_choice", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, question_index: u8)]
+pub struct RecordChoice<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        constraint = response.respondent == payer.key() @ ErrorCode::Unauthorized,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(
+        seeds = [b"choice_tally", survey.key().as_ref(), &[question_index]],
+        bump = choice_tally.bump,
+        constraint = choice_tally.is_initialized @ ErrorCode::ChoiceTallyNotInitialized
+    )]
+    pub choice_tally: Box<Account<'info, ChoiceTally>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_RECORD_CHOICE))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("record_choice", payer)]
+#[derive(Accounts)]
+pub struct RecordChoiceCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_RECORD_CHOICE))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"choice_tally", choice_tally.survey.as_ref(), &[choice_tally.question_index]],
+        bump = choice_tally.bump
+    )]
+    pub choice_tally: Box<Account<'info, ChoiceTally>>,
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+}
+
+#[init_computation_definition_accounts("record_choice", payer)]
+#[derive(Accounts)]
+pub struct InitRecordChoiceCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("reveal_choice_tally", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RevealChoiceTally<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        address = choice_tally.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        seeds = [b"choice_tally", choice_tally.survey.as_ref(), &[choice_tally.question_index]],
+        bump = choice_tally.bump,
+        constraint = choice_tally.is_initialized @ ErrorCode::ChoiceTallyNotInitialized
+    )]
+    pub choice_tally: Box<Account<'info, ChoiceTally>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_CHOICE_TALLY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("reveal_choice_tally", payer)]
+#[derive(Accounts)]
+pub struct RevealChoiceTallyCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_CHOICE_TALLY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        seeds = [b"choice_tally", choice_tally.survey.as_ref(), &[choice_tally.question_index]],
+        bump = choice_tally.bump
+    )]
+    pub choice_tally: Box<Account<'info, ChoiceTally>>,
+}
+
+#[init_computation_definition_accounts("reveal_choice_tally", payer)]
+#[derive(Accounts)]
+pub struct InitRevealChoiceTallyCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct PrivacyBudgetSpentEvent {
+    pub survey: Pubkey,
+    pub epsilon_milli: u64,
+    pub remaining: u64,
+}
+
+// The respondent may record the question again with a valid option
+#[event]
+pub struct ChoiceRejectedEvent {
+    pub response: Pubkey,
+    pub question_index: u8,
+}
+
+// The respondent may record the pair again with valid options
+#[event]
+pub struct CrossTabRejectedEvent {
+    pub response: Pubkey,
+    pub table_index: u8,
+}
+
+#[event]
+pub struct ChoiceTallyRevealedEvent {
+    pub survey: Pubkey,
+    pub question_index: u8,
+    pub counts: [[u8; 32]; MAX_CHOICE_OPTIONS],
+    pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+}
+
+#[queue_computation_accounts("init_rating_stats", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, question_index: u8)]
+pub struct InitRatingStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<RatingStats>(),
+        seeds = [b"rating_stats", survey.key().as_ref(), &[question_index]],
+        bump
+    )]
+    pub rating_stats: Box<Account<'info, RatingStats>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_RATING_STATS))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("init_rating_stats", payer)]
+#[derive(Accounts)]
+pub struct InitRatingStatsCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_RATING_STATS))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"rating_stats", rating_stats.survey.as_ref(), &[rating_stats.question_index]],
+        bump = rating_stats.bump
+    )]
+    pub rating_stats: Box<Account<'info, RatingStats>>,
+}
+
+#[init_computation_definition_accounts("init_rating_stats", payer)]
+#[derive(Accounts)]
+pub struct InitRatingStatsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("record_rating", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, question_index: u8)]
+pub struct RecordRating<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        constraint = response.respondent == payer.key() @ ErrorCode::Unauthorized,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(
+        seeds = [b"rating_stats", survey.key().as_ref(), &[question_index]],
+        bump = rating_stats.bump,
+        constraint = rating_stats.is_initialized @ ErrorCode::RatingStatsNotInitialized
+    )]
+    pub rating_stats: Box<Account<'info, RatingStats>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_RECORD_RATING))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("record_rating", payer)]
+#[derive(Accounts)]
+pub struct RecordRatingCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_RECORD_RATING))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"rating_stats", rating_stats.survey.as_ref(), &[rating_stats.question_index]],
+        bump = rating_stats.bump
+    )]
+    pub rating_stats: Box<Account<'info, RatingStats>>,
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+}
+
+#[init_computation_definition_accounts("record_rating", payer)]
+#[derive(Accounts)]
+pub struct InitRecordRatingCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("finalize_rating_stats", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct FinalizeRatingStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        address = rating_stats.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        seeds = [b"rating_stats", rating_stats.survey.as_ref(), &[rating_stats.question_index]],
+        bump = rating_stats.bump,
+        constraint = rating_stats.is_initialized @ ErrorCode::RatingStatsNotInitialized
+    )]
+    pub rating_stats: Box<Account<'info, RatingStats>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_RATING_STATS))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
         space = 8 + size_of::<ComputationResult>(),
-        seeds = [b"computation_result", payer.key().as_ref()],
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
         bump
     )]
     pub computation_result: Account<'info, ComputationResult>,
     pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("finalize_rating_stats", payer)]
+#[derive(Accounts)]
+pub struct FinalizeRatingStatsCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_RATING_STATS))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: Sysvar account provided by runtime; verified by address constraint above.
+    /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        seeds = [b"rating_stats", rating_stats.survey.as_ref(), &[rating_stats.question_index]],
+        bump = rating_stats.bump
+    )]
+    pub rating_stats: Box<Account<'info, RatingStats>>,
 }
 
-#[init_computation_definition_accounts("add_together", payer)]
+#[init_computation_definition_accounts("finalize_rating_stats", payer)]
 #[derive(Accounts)]
-pub struct InitAddTogetherCompDef<'info> {
+pub struct InitFinalizeRatingStatsCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
     #[account(mut, address = derive_mxe_pda!())]
     pub mxe_account: Box<Account<'info, MXEAccount>>,
     #[account(mut)]
@@ -299,12 +5804,41 @@ pub struct InitAddTogetherCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[queue_computation_accounts("submit_response", payer)]
+#[event]
+pub struct RatingSummaryEvent {
+    pub survey: Pubkey,
+    pub question_index: u8,
+    pub summary: [[u8; 32]; 3],
+    pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+}
+
+#[queue_computation_accounts("init_cross_tab", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct SubmitResponse<'info> {
+#[instruction(computation_offset: u64, table_index: u8)]
+pub struct InitCrossTab<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<CrossTab>(),
+        seeds = [b"cross_tab", survey.key().as_ref(), &[table_index]],
+        bump
+    )]
+    pub cross_tab: Box<Account<'info, CrossTab>>,
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
     #[account(mut, address = derive_mempool_pda!())]
@@ -316,7 +5850,7 @@ pub struct SubmitResponse<'info> {
     #[account(mut, address = derive_comp_pda!(computation_offset))]
     /// CHECK: This is a PDA managed by the Arcium program and validated there.
     pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_RESPONSE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_CROSS_TAB))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(mut, address = derive_cluster_pda!(mxe_account))]
     pub cluster_account: Account<'info, Cluster>,
@@ -324,72 +5858,107 @@ pub struct SubmitResponse<'info> {
     pub pool_account: Account<'info, FeePool>,
     #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
     pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
     pub system_program: Program<'info, System>,
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[callback_accounts("submit_response", payer)]
+#[callback_accounts("init_cross_tab", payer)]
 #[derive(Accounts)]
-pub struct SubmitResponseCallback<'info> {
+pub struct InitCrossTabCallback<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_RESPONSE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_CROSS_TAB))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
     #[account(
-        init,
-        payer = payer,
-        space = 8 + size_of::<ComputationResult>(),
-        seeds = [b"computation_result", payer.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
     )]
     pub computation_result: Account<'info, ComputationResult>,
-    pub system_program: Program<'info, System>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: Sysvar account provided by runtime; verified by address constraint above.
-    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"cross_tab", cross_tab.survey.as_ref(), &[cross_tab.table_index]],
+        bump = cross_tab.bump
+    )]
+    pub cross_tab: Box<Account<'info, CrossTab>>,
+    #[account(
+        mut,
+        address = cross_tab.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
 }
 
-#[init_computation_definition_accounts("submit_response", payer)]
+#[init_computation_definition_accounts("init_cross_tab", payer)]
 #[derive(Accounts)]
-pub struct InitSubmitResponseCompDef<'info> {
+pub struct InitCrossTabCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
     #[account(mut, address = derive_mxe_pda!())]
     pub mxe_account: Box<Account<'info, MXEAccount>>,
     #[account(mut)]
-    /// CHECK: This account will be created by the Arcium program with correct seeds and type via CPI.
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
     pub comp_def_account: UncheckedAccount<'info>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
 
-#[event]
-pub struct SumEvent {
-    pub sum: [u8; 32],
-    pub nonce: [u8; 16],
-}
-
-#[event]
-pub struct ResponseEvent {
-    pub response: [u8; 32],
-    pub nonce: [u8; 16],
-}
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("The computation was aborted")]
-    AbortedComputation,
-    #[msg("Cluster not set")]
-    ClusterNotSet,
-}
-
-#[queue_computation_accounts("create_survey", payer)]
+#[queue_computation_accounts("record_cross_tab", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct CreateSurvey<'info> {
+#[instruction(computation_offset: u64, table_index: u8)]
+pub struct RecordCrossTab<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        address = response.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        mut,
+        constraint = response.respondent == payer.key() @ ErrorCode::Unauthorized,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+    #[account(
+        seeds = [b"cross_tab", survey.key().as_ref(), &[table_index]],
+        bump = cross_tab.bump,
+        constraint = cross_tab.is_initialized @ ErrorCode::CrossTabNotInitialized
+    )]
+    pub cross_tab: Box<Account<'info, CrossTab>>,
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
     #[account(mut, address = derive_mempool_pda!())]
@@ -401,7 +5970,7 @@ pub struct CreateSurvey<'info> {
     #[account(mut, address = derive_comp_pda!(computation_offset))]
     /// CHECK: This is a PDA managed by the Arcium program and validated there.
     pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_SURVEY))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_RECORD_CROSS_TAB))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(mut, address = derive_cluster_pda!(mxe_account))]
     pub cluster_account: Account<'info, Cluster>,
@@ -409,37 +5978,166 @@ pub struct CreateSurvey<'info> {
     pub pool_account: Account<'info, FeePool>,
     #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
     pub clock_account: Account<'info, ClockAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + size_of::<ComputationResult>(),
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
     pub system_program: Program<'info, System>,
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[callback_accounts("create_survey", payer)]
+#[callback_accounts("record_cross_tab", payer)]
 #[derive(Accounts)]
-pub struct CreateSurveyCallback<'info> {
+pub struct RecordCrossTabCallback<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_SURVEY))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_RECORD_CROSS_TAB))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        mut,
+        seeds = [b"cross_tab", cross_tab.survey.as_ref(), &[cross_tab.table_index]],
+        bump = cross_tab.bump
+    )]
+    pub cross_tab: Box<Account<'info, CrossTab>>,
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            response.survey.as_ref(),
+            response.respondent.as_ref(),
+            response.sequence.to_le_bytes().as_ref()
+        ],
+        bump = response.bump
+    )]
+    pub response: Box<Account<'info, Response>>,
+}
+
+#[init_computation_definition_accounts("record_cross_tab", payer)]
+#[derive(Accounts)]
+pub struct InitRecordCrossTabCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("reveal_cross_tab", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RevealCrossTab<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        address = cross_tab.survey,
+        seeds = [b"survey", survey.creator.as_ref(), survey.survey_id.to_le_bytes().as_ref()],
+        bump = survey.bump
+    )]
+    pub survey: Box<Account<'info, Survey>>,
+    #[account(
+        seeds = [b"cross_tab", cross_tab.survey.as_ref(), &[cross_tab.table_index]],
+        bump = cross_tab.bump,
+        constraint = cross_tab.is_initialized @ ErrorCode::CrossTabNotInitialized
+    )]
+    pub cross_tab: Box<Account<'info, CrossTab>>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: This is a PDA managed by the Arcium program and validated there.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_CROSS_TAB))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
     #[account(
         init,
         payer = payer,
         space = 8 + size_of::<ComputationResult>(),
-        seeds = [b"computation_result", payer.key().as_ref()],
+        seeds = [b"computation_result", computation_offset.to_le_bytes().as_ref()],
         bump
     )]
     pub computation_result: Account<'info, ComputationResult>,
     pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("reveal_cross_tab", payer)]
+#[derive(Accounts)]
+pub struct RevealCrossTabCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_CROSS_TAB))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"computation_result", computation_result.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_result.bump
+    )]
+    pub computation_result: Account<'info, ComputationResult>,
+    #[account(
+        seeds = [b"cross_tab", cross_tab.survey.as_ref(), &[cross_tab.table_index]],
+        bump = cross_tab.bump
+    )]
+    pub cross_tab: Box<Account<'info, CrossTab>>,
 }
 
-#[init_computation_definition_accounts("create_survey", payer)]
+#[init_computation_definition_accounts("reveal_cross_tab", payer)]
 #[derive(Accounts)]
-pub struct InitCreateSurveyCompDef<'info> {
+pub struct InitRevealCrossTabCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Box<Account<'info, Config>>,
     #[account(mut, address = derive_mxe_pda!())]
     pub mxe_account: Box<Account<'info, MXEAccount>>,
     #[account(mut)]
@@ -450,9 +6148,397 @@ pub struct InitCreateSurveyCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-
 #[event]
-pub struct SurveyCreatedEvent {
-    pub survey_id: [u8; 32],
+pub struct CrossTabRevealedEvent {
+    pub survey: Pubkey,
+    pub table_index: u8,
+    pub table: [[u8; 32]; CROSS_TAB_CELLS],
     pub nonce: [u8; 16],
+    pub recipient: [u8; 32],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_response() -> Response {
+        Response {
+            processed: [[0xAB; 32]; 4],
+            survey: Pubkey::new_unique(),
+            respondent: Pubkey::new_unique(),
+            sequence: 3,
+            index: 9,
+            ciphertexts: [[1u8; 32]; 5],
+            nonce: [2u8; 16],
+            encryption_key: [3u8; 32],
+            processed_nonce: [4u8; 16],
+            loan_officer_key: [5u8; 32],
+            eligibility: [[6u8; 32]; 3],
+            eligibility_nonce: [7u8; 16],
+            submitted_slot: 11,
+            computation_offset: 12,
+            status: ComputationStatus::Completed,
+            queued_at: 10,
+            reward_account: Pubkey::new_unique(),
+            reward_mode: RewardMode::QualityWeighted,
+            reward_amount: 13,
+            reward_settled: false,
+            reward_paid: false,
+            reward_claim_pending: false,
+            recorded_choices: 0,
+            recorded_ratings: 0,
+            bump: 255,
+        }
+    }
+
+    fn sample_survey() -> Survey {
+        Survey {
+            creator: Pubkey::new_unique(),
+            survey_id: 1,
+            schema_hash: [0u8; 32],
+            metrics: [[0u8; 32]; 6],
+            metrics_nonce: [0u8; 16],
+            analyst_key: [0u8; 32],
+            analysis: [[0u8; 32]; 4],
+            analysis_nonce: [0u8; 16],
+            settings: SurveySettings {
+                starts_at: None,
+                ends_at: None,
+                max_responses: None,
+                response_mode: ResponseMode::Single,
+                gate: None,
+                min_reveal_responses: 1,
+                privacy_budget: 1000,
+            },
+            allowlist_root: None,
+            status: SurveyStatus::Closed,
+            created_at: 0,
+            opened_at: 0,
+            closed_at: 0,
+            response_count: 0,
+            reward_mint: None,
+            reward_mode: RewardMode::Fixed,
+            reward_per_response: 0,
+            rewards_committed: 0,
+            members: [SurveyMember::EMPTY; MAX_SURVEY_MEMBERS],
+            member_count: 0,
+            privacy_spent: 0,
+            aggregate_accounts: 0,
+            response_accounts: 0,
+            cross_tab_questions: 0,
+            bump: 255,
+        }
+    }
+
+    fn sample_config() -> Config {
+        Config {
+            authority: Pubkey::new_unique(),
+            pending_authority: None,
+            paused: false,
+            protocol_fee_lamports: 0,
+            treasury: Pubkey::new_unique(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn response_processed_offset_matches_layout() {
+        let response = sample_response();
+        let mut data = Vec::new();
+        response.try_serialize(&mut data).unwrap();
+
+        let start = RESPONSE_PROCESSED_OFFSET as usize;
+        assert_eq!(&data[..8], Response::DISCRIMINATOR);
+        assert_eq!(&data[start..start + 32 * 4], response.processed.concat().as_slice());
+    }
+
+    #[test]
+    fn aggregate_accounts_close_only_after_survey_closes() {
+        let config = sample_config();
+        let mut survey = sample_survey();
+        survey.status = SurveyStatus::Open;
+        survey.open_aggregate().unwrap();
+        survey.open_aggregate().unwrap();
+        let creator = survey.creator;
+
+        let res = survey.close_aggregate(&creator, &config);
+        assert_eq!(res.unwrap_err(), ErrorCode::InvalidSurveyStatus.into());
+
+        survey.status = SurveyStatus::Closed;
+        let res = survey.close_aggregate(&Pubkey::new_unique(), &config);
+        assert_eq!(res.unwrap_err(), ErrorCode::Unauthorized.into());
+
+        survey.close_aggregate(&creator, &config).unwrap();
+        survey.close_aggregate(&config.authority, &config).unwrap();
+        assert_eq!(survey.aggregate_accounts, 0);
+    }
+
+    #[test]
+    fn aggregate_accounts_close_for_co_owners_only() {
+        let config = sample_config();
+        let mut survey = sample_survey();
+        survey.open_aggregate().unwrap();
+        let (owner, analyst) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for (key, role) in [(owner, SurveyRole::Owner), (analyst, SurveyRole::Analyst)] {
+            survey
+                .add_member(SurveyMember {
+                    key,
+                    role,
+                    encryption_key: [0u8; 32],
+                })
+                .unwrap();
+        }
+
+        let res = survey.close_aggregate(&analyst, &config);
+        assert_eq!(res.unwrap_err(), ErrorCode::Unauthorized.into());
+        survey.close_aggregate(&owner, &config).unwrap();
+        assert_eq!(survey.aggregate_accounts, 0);
+    }
+
+    #[test]
+    fn quiz_whitelist_gate_requires_a_valid_matching_whitelist() {
+        let (quiz, respondent) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let gate = ParticipationGate::QuizWhitelist { quiz };
+        let mut whitelist = Whitelist {
+            quiz,
+            wallet: respondent,
+            issued: true,
+            issued_at: 100,
+            expires_at: Some(200),
+            bump: 255,
+        };
+
+        let res = gate.check(&respondent, None, None, None, 150);
+        assert_eq!(res.unwrap_err(), ErrorCode::MissingGateAccount.into());
+        gate.check(&respondent, None, None, Some(&whitelist), 150).unwrap();
+
+        let res = gate.check(&Pubkey::new_unique(), None, None, Some(&whitelist), 150);
+        assert_eq!(res.unwrap_err(), ErrorCode::ParticipationGateNotMet.into());
+        let res = gate.check(&respondent, None, None, Some(&whitelist), 200);
+        assert_eq!(res.unwrap_err(), ErrorCode::ParticipationGateNotMet.into());
+
+        whitelist.expires_at = None;
+        gate.check(&respondent, None, None, Some(&whitelist), i64::MAX).unwrap();
+        whitelist.issued = false;
+        let res = gate.check(&respondent, None, None, Some(&whitelist), 150);
+        assert_eq!(res.unwrap_err(), ErrorCode::ParticipationGateNotMet.into());
+    }
+
+    #[test]
+    fn token_gates_require_a_token_account() {
+        let respondent = Pubkey::new_unique();
+        let gate = ParticipationGate::TokenBalance {
+            mint: Pubkey::new_unique(),
+            min_amount: 1,
+        };
+        let res = gate.check(&respondent, None, None, None, 0);
+        assert_eq!(res.unwrap_err(), ErrorCode::MissingGateAccount.into());
+    }
+
+    #[test]
+    fn zeroed_quiz_attempt_reads_as_not_started() {
+        let data = [0u8; size_of::<QuizAttempt>()];
+        let attempt = QuizAttempt::deserialize(&mut &data[..]).unwrap();
+        assert!(attempt.status == QuizAttemptStatus::NotStarted);
+        assert_eq!(attempt.attempt_count, 0);
+    }
+
+    #[test]
+    fn survey_owner_or_config_authority_check() {
+        let config = sample_config();
+        let mut survey = sample_survey();
+        let (co_owner, auditor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for (key, role) in [(co_owner, SurveyRole::Owner), (auditor, SurveyRole::Auditor)] {
+            survey
+                .add_member(SurveyMember {
+                    key,
+                    role,
+                    encryption_key: [0u8; 32],
+                })
+                .unwrap();
+        }
+
+        for signer in [survey.creator, co_owner, config.authority] {
+            survey.require_owner_or_config_authority(&signer, &config).unwrap();
+        }
+        for signer in [auditor, Pubkey::new_unique()] {
+            let res = survey.require_owner_or_config_authority(&signer, &config);
+            assert_eq!(res.unwrap_err(), ErrorCode::Unauthorized.into());
+        }
+        survey
+            .require_role(&auditor, &[SurveyRole::Owner, SurveyRole::Analyst, SurveyRole::Auditor])
+            .unwrap();
+    }
+
+    #[test]
+    fn receipt_hash_commits_to_the_stored_result() {
+        let mut result = ComputationResult {
+            computation_id: [1u8; 32],
+            computation_offset: 7,
+            computation_type: ComputationType::RevealChoiceTally,
+            status: ComputationStatus::Completed,
+            result_data: [2u8; 32],
+            nonce: [3u8; 16],
+            created_at: 10,
+            completed_at: 20,
+            creator: Pubkey::new_unique(),
+            survey_id: None,
+            response_id: None,
+            failure_reason: None,
+            bump: 255,
+        };
+        let hash = result.receipt_hash();
+        assert_eq!(hash, result.receipt_hash());
+
+        result.result_data[0] ^= 1;
+        assert_ne!(hash, result.receipt_hash());
+    }
+
+    #[test]
+    fn record_bits_are_claimed_once_and_released() {
+        let mut mask = 0u32;
+        Response::claim_record(&mut mask, 3, ErrorCode::ChoiceAlreadyRecorded).unwrap();
+        let res = Response::claim_record(&mut mask, 3, ErrorCode::ChoiceAlreadyRecorded);
+        assert_eq!(res.unwrap_err(), ErrorCode::ChoiceAlreadyRecorded.into());
+        Response::claim_record(&mut mask, 31, ErrorCode::ChoiceAlreadyRecorded).unwrap();
+        assert_eq!(mask, (1 << 3) | (1 << 31));
+
+        let res = Response::claim_record(&mut mask, 32, ErrorCode::ChoiceAlreadyRecorded);
+        assert_eq!(res.unwrap_err(), ErrorCode::InvalidQuestionIndex.into());
+
+        Response::release_record(&mut mask, 3);
+        assert_eq!(mask, 1 << 31);
+        Response::claim_record(&mut mask, 3, ErrorCode::ChoiceAlreadyRecorded).unwrap();
+    }
+
+    #[test]
+    fn cross_tab_questions_bind_to_one_table() {
+        let mut survey = sample_survey();
+        survey.bind_cross_tab(0, 5).unwrap();
+        assert!(survey.in_cross_tab(0) && survey.in_cross_tab(5));
+        assert!(!survey.in_cross_tab(1) && !survey.in_cross_tab(32));
+
+        let res = survey.bind_cross_tab(5, 6);
+        assert_eq!(res.unwrap_err(), ErrorCode::QuestionInCrossTab.into());
+        assert!(!survey.in_cross_tab(6));
+
+        survey.unbind_cross_tab(0, 5);
+        assert_eq!(survey.cross_tab_questions, 0);
+        survey.bind_cross_tab(5, 6).unwrap();
+    }
+
+    #[test]
+    fn noise_thresholds_match_digit_probabilities() {
+        let pairs = [(1000, 1), (500, 1), (100, 1), (3000, 1), (333, 10), (333, 100), (10_000, 1)];
+        for (epsilon_milli, sensitivity) in pairs {
+            let thresholds = noise_thresholds(epsilon_milli, sensitivity).unwrap();
+            let alpha = (-(epsilon_milli as f64) / 1000.0 / sensitivity as f64).exp();
+            for (k, threshold) in thresholds.iter().enumerate() {
+                let power = alpha.powi(1 << k);
+                let expected = power / (1.0 + power) * (1u64 << NOISE_BITS) as f64;
+                assert!(
+                    (*threshold as f64 - expected).abs() <= 1.0,
+                    "eps {epsilon_milli} sens {sensitivity} digit {k}: {threshold} vs {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn noise_thresholds_vanish_for_large_scales() {
+        assert_eq!(noise_thresholds(100_000, 1).unwrap(), [0u64; NOISE_DIGITS]);
+    }
+
+    #[test]
+    fn noise_thresholds_reject_noise_past_the_sampled_digits() {
+        // alpha^(2^16) = exp(-6.55) and exp(-13.1) around the 2^-16 cut-off
+        let res = noise_thresholds(1, 10);
+        assert_eq!(res.unwrap_err(), ErrorCode::NoiseScaleTooLarge.into());
+        noise_thresholds(2, 10).unwrap();
+
+        let res = noise_thresholds(1, MAX_RATING * MAX_RATING);
+        assert_eq!(res.unwrap_err(), ErrorCode::NoiseScaleTooLarge.into());
+        let res = noise_thresholds(1, u64::MAX);
+        assert_eq!(res.unwrap_err(), ErrorCode::NoiseScaleTooLarge.into());
+    }
+
+    #[test]
+    fn spend_privacy_stops_at_the_budget() {
+        let mut survey = sample_survey();
+        assert_eq!(survey.spend_privacy(600).unwrap(), 400);
+        assert_eq!(survey.spend_privacy(400).unwrap(), 0);
+
+        let res = survey.spend_privacy(1);
+        assert_eq!(res.unwrap_err(), ErrorCode::PrivacyBudgetExhausted.into());
+        assert_eq!(survey.privacy_spent, 1000);
+    }
+
+    #[test]
+    fn spend_privacy_rejects_zero_and_overflow() {
+        let mut survey = sample_survey();
+        let res = survey.spend_privacy(0);
+        assert_eq!(res.unwrap_err(), ErrorCode::InvalidPrivacyBudget.into());
+
+        survey.settings.privacy_budget = u64::MAX;
+        survey.privacy_spent = u64::MAX - 1;
+        let res = survey.spend_privacy(2);
+        assert_eq!(res.unwrap_err(), ErrorCode::Overflow.into());
+        assert_eq!(survey.privacy_spent, u64::MAX - 1);
+    }
+
+    #[test]
+    fn spend_privacy_charges_every_queued_release() {
+        // Releases are charged when queued and reveal callbacks never refund,
+        // so retrying a failed release spends the budget again
+        let mut survey = sample_survey();
+        for remaining in [750, 500, 250, 0] {
+            assert_eq!(survey.spend_privacy(250).unwrap(), remaining);
+        }
+        let res = survey.spend_privacy(250);
+        assert_eq!(res.unwrap_err(), ErrorCode::PrivacyBudgetExhausted.into());
+    }
+
+    #[test]
+    fn aborted_responses_do_not_count_against_the_limit() {
+        let mut receipt = ResponseReceipt {
+            survey: Pubkey::new_unique(),
+            respondent: Pubkey::new_unique(),
+            response_count: 1,
+            failed_count: 0,
+            last_submitted_at: 0,
+            bump: 255,
+        };
+        assert_eq!(receipt.counted_responses(), 1);
+        receipt.failed_count = 1;
+        assert_eq!(receipt.counted_responses(), 0);
+        receipt.response_count = 2;
+        assert_eq!(receipt.counted_responses(), 1);
+    }
+
+    #[test]
+    fn quiz_counts_open_accounts_and_idles_after_the_stale_window() {
+        let mut quiz = Quiz {
+            answer_key: [[0u8; 32]; 2 * MAX_QUIZ_QUESTIONS],
+            answer_key_nonce: 0,
+            creator: Pubkey::new_unique(),
+            quiz_id: 1,
+            question_count: 1,
+            minimum_score: 1,
+            whitelist_validity: None,
+            max_attempts: 1,
+            time_limit: None,
+            key_uploaded: true,
+            key_upload_pending: false,
+            created_at: 0,
+            last_queued_at: 100,
+            open_accounts: u32::MAX - 1,
+            bump: 255,
+        };
+        quiz.open_account().unwrap();
+        let res = quiz.open_account();
+        assert_eq!(res.unwrap_err(), ErrorCode::Overflow.into());
+
+        assert!(!quiz.is_idle(100 + STALE_COMPUTATION_SECS - 1));
+        assert!(quiz.is_idle(100 + STALE_COMPUTATION_SECS));
+    }
 }
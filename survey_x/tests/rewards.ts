@@ -0,0 +1,324 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import {
+  createMint,
+  createAccount,
+  mintTo,
+  closeAccount,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { SurveyX } from "../target/types/survey_x";
+import { randomBytes } from "crypto";
+import {
+  awaitComputationFinalization,
+  getArciumEnv,
+  getCompDefAccOffset,
+  getArciumAccountBaseSeed,
+  getArciumProgAddress,
+  buildFinalizeCompDefTx,
+  RescueCipher,
+  deserializeLE,
+  getMXEPublicKey,
+  getMXEAccAddress,
+  getMempoolAccAddress,
+  getCompDefAccAddress,
+  getExecutingPoolAccAddress,
+  getComputationAccAddress,
+  x25519,
+} from "@arcium-hq/client";
+import * as fs from "fs";
+import * as os from "os";
+import { expect } from "chai";
+
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+describe("SurveyX rewards", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.SurveyX as Program<SurveyX>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const arciumEnv = getArciumEnv();
+  const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+
+  const pda = (...seeds: (Buffer | Uint8Array)[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const u64Le = (value: anchor.BN) => value.toArrayLike(Buffer, "le", 8);
+  const u16Le = (value: number) => new anchor.BN(value).toArrayLike(Buffer, "le", 2);
+
+  const arciumAccounts = (circuit: string, computationOffset: anchor.BN) => ({
+    computationAccount: getComputationAccAddress(
+      program.programId,
+      computationOffset
+    ),
+    clusterAccount: arciumEnv.arciumClusterPubkey,
+    mxeAccount: getMXEAccAddress(program.programId),
+    mempoolAccount: getMempoolAccAddress(program.programId),
+    executingPool: getExecutingPoolAccAddress(program.programId),
+    compDefAccount: getCompDefAccAddress(
+      program.programId,
+      Buffer.from(getCompDefAccOffset(circuit)).readUInt32LE()
+    ),
+    computationResult: pda(Buffer.from("computation_result"), u64Le(computationOffset)),
+  });
+
+  async function initCompDef(circuit: string, method: string) {
+    const offset = getCompDefAccOffset(circuit);
+    const compDefAccount = PublicKey.findProgramAddressSync(
+      [
+        getArciumAccountBaseSeed("ComputationDefinitionAccount"),
+        program.programId.toBuffer(),
+        offset,
+      ],
+      getArciumProgAddress()
+    )[0];
+    if (await provider.connection.getAccountInfo(compDefAccount)) {
+      return;
+    }
+    await program.methods[method]()
+      .accountsPartial({
+        compDefAccount,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+
+    const finalizeTx = await buildFinalizeCompDefTx(
+      provider,
+      Buffer.from(offset).readUInt32LE(),
+      program.programId
+    );
+    const latestBlockhash = await provider.connection.getLatestBlockhash();
+    finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+    finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+    finalizeTx.sign(owner);
+    await provider.sendAndConfirm(finalizeTx);
+  }
+
+  async function finalize(computationOffset: anchor.BN) {
+    await awaitComputationFinalization(
+      provider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
+  }
+
+  it("records the response even if the reward account is closed before payout", async () => {
+    const config = pda(Buffer.from("config"));
+    if (!(await provider.connection.getAccountInfo(config))) {
+      await program.methods
+        .initializeConfig(owner.publicKey, new anchor.BN(0))
+        .accountsPartial({
+          authority: owner.publicKey,
+          config,
+          programData: PublicKey.findProgramAddressSync(
+            [program.programId.toBuffer()],
+            BPF_LOADER_UPGRADEABLE_ID
+          )[0],
+        })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+    }
+    await initCompDef("create_survey", "initCreateSurveyCompDef");
+    await initCompDef("init_survey_analytics", "initSurveyAnalyticsCompDef");
+    await initCompDef("submit_response", "initSubmitResponseCompDef");
+
+    const mxePublicKey = await getMXEPublicKey(provider, program.programId);
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    // Create and open a survey paying a fixed reward of 10 tokens
+    const surveyId = new anchor.BN(randomBytes(8), "hex");
+    const survey = pda(Buffer.from("survey"), owner.publicKey.toBuffer(), u64Le(surveyId));
+    const surveyNonce = randomBytes(16);
+    const surveyCiphertexts = cipher.encrypt(
+      [10, 20, 5, 6, 3, 12].map(BigInt),
+      surveyNonce
+    );
+    const createOffset = new anchor.BN(randomBytes(8), "hex");
+    await program.methods
+      .createSurvey(
+        createOffset,
+        surveyId,
+        Array.from(randomBytes(32)),
+        {
+          startsAt: null,
+          endsAt: null,
+          maxResponses: null,
+          responseMode: { single: {} },
+          gate: null,
+          minRevealResponses: new anchor.BN(1),
+          privacyBudget: new anchor.BN(1000),
+        },
+        ...surveyCiphertexts.map((c) => Array.from(c)),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(surveyNonce).toString()),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(randomBytes(16)).toString())
+      )
+      .accountsPartial({
+        payer: owner.publicKey,
+        config,
+        treasury: owner.publicKey,
+        survey,
+        ...arciumAccounts("create_survey", createOffset),
+      })
+      .signers([owner])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await finalize(createOffset);
+
+    const analyticsOffset = new anchor.BN(randomBytes(8), "hex");
+    await program.methods
+      .initSurveyAnalytics(
+        analyticsOffset,
+        new anchor.BN(deserializeLE(randomBytes(16)).toString())
+      )
+      .accountsPartial({
+        payer: owner.publicKey,
+        config,
+        survey,
+        surveyAnalytics: pda(Buffer.from("survey_analytics"), survey.toBuffer()),
+        ...arciumAccounts("init_survey_analytics", analyticsOffset),
+      })
+      .signers([owner])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await finalize(analyticsOffset);
+
+    await program.methods
+      .openSurvey()
+      .accountsPartial({ authority: owner.publicKey, config, survey })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+
+    const mint = await createMint(provider.connection, owner, owner.publicKey, null, 0);
+    const creatorTokenAccount = await createAccount(
+      provider.connection,
+      owner,
+      mint,
+      owner.publicKey
+    );
+    await mintTo(provider.connection, owner, mint, creatorTokenAccount, owner, 100);
+    const rewardVault = pda(Buffer.from("reward_vault"), survey.toBuffer());
+    await program.methods
+      .fundRewardVault({ fixed: {} }, new anchor.BN(10), new anchor.BN(100))
+      .accountsPartial({
+        authority: owner.publicKey,
+        config,
+        survey,
+        rewardMint: mint,
+        rewardVault,
+        authorityTokenAccount: creatorTokenAccount,
+      })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+
+    // Submit a response, then close the reward account while it is processed
+    const respondent = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(respondent.publicKey, 2e9),
+      "confirmed"
+    );
+    const respondentTokenAccount = await createAccount(
+      provider.connection,
+      respondent,
+      mint,
+      respondent.publicKey,
+      Keypair.generate()
+    );
+    const response = pda(
+      Buffer.from("response"),
+      survey.toBuffer(),
+      respondent.publicKey.toBuffer(),
+      u16Le(0)
+    );
+    const responseNonce = randomBytes(16);
+    const responseCiphertexts = cipher.encrypt(
+      [42, 8, 120, 3, Math.floor(Date.now() / 1000)].map(BigInt),
+      responseNonce
+    );
+    const submitOffset = new anchor.BN(randomBytes(8), "hex");
+    await program.methods
+      .submitResponse(
+        submitOffset,
+        ...responseCiphertexts.map((c) => Array.from(c)),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(responseNonce).toString()),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(randomBytes(16)).toString()),
+        []
+      )
+      .accountsPartial({
+        payer: respondent.publicKey,
+        config,
+        survey,
+        responseReceipt: pda(
+          Buffer.from("response_receipt"),
+          survey.toBuffer(),
+          respondent.publicKey.toBuffer()
+        ),
+        response,
+        surveyAnalytics: pda(Buffer.from("survey_analytics"), survey.toBuffer()),
+        ...arciumAccounts("submit_response", submitOffset),
+        gateTokenAccount: null,
+        gateMetadata: null,
+        gateWhitelist: null,
+        rewardVault,
+        respondentRewardAccount: respondentTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([respondent])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await closeAccount(
+      provider.connection,
+      respondent,
+      respondentTokenAccount,
+      respondent.publicKey,
+      respondent
+    );
+    await finalize(submitOffset);
+
+    // The MPC result is recorded and the reward stays reserved, unpaid
+    const processed = await program.account.response.fetch(response);
+    expect(processed.status).to.deep.equal({ completed: {} });
+    expect(processed.rewardSettled).to.be.true;
+    expect(processed.rewardPaid).to.be.false;
+    expect(
+      (await program.account.survey.fetch(survey)).rewardsCommitted.toNumber()
+    ).to.equal(10);
+
+    // Paying into the closed account fails without touching the response
+    let payError: unknown;
+    try {
+      await program.methods
+        .payResponseReward()
+        .accountsPartial({
+          config,
+          survey,
+          response,
+          rewardVault,
+          respondentRewardAccount: respondentTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (error) {
+      payError = error;
+    }
+    expect(payError).to.exist;
+    const unpaid = await program.account.response.fetch(response);
+    expect(unpaid.rewardPaid).to.be.false;
+    expect(
+      (await program.account.survey.fetch(survey)).rewardsCommitted.toNumber()
+    ).to.equal(10);
+  });
+});
+
+function readKpJson(path: string): anchor.web3.Keypair {
+  const file = fs.readFileSync(path);
+  return anchor.web3.Keypair.fromSecretKey(
+    new Uint8Array(JSON.parse(file.toString()))
+  );
+}
@@ -25,6 +25,10 @@ import * as fs from "fs";
 import * as os from "os";
 import { expect } from "chai";
 
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
 describe("SurveyX", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
@@ -47,11 +51,34 @@ describe("SurveyX", () => {
     return event;
   };
 
-  // Skip Arcium environment setup for basic deployment test
-  const arciumEnv = null;
+  const arciumEnv = getArciumEnv();
+  const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+  const config = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  )[0];
+
+  // Every instruction reads the program config, so create it unless another
+  // suite already has
+  before(async () => {
+    if (await provider.connection.getAccountInfo(config)) {
+      return;
+    }
+    await program.methods
+      .initializeConfig(owner.publicKey, new anchor.BN(0))
+      .accountsPartial({
+        authority: owner.publicKey,
+        config,
+        programData: PublicKey.findProgramAddressSync(
+          [program.programId.toBuffer()],
+          BPF_LOADER_UPGRADEABLE_ID
+        )[0],
+      })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+  });
 
   it("Is initialized!", async () => {
-    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
 
     console.log("Initializing add together computation definition");
     const initATSig = await initAddTogetherCompDef(
@@ -97,6 +124,7 @@ describe("SurveyX", () => {
         new anchor.BN(deserializeLE(nonce).toString())
       )
       .accountsPartial({
+        config,
         computationAccount: getComputationAccAddress(
           program.programId,
           computationOffset
@@ -109,6 +137,13 @@ describe("SurveyX", () => {
           program.programId,
           Buffer.from(getCompDefAccOffset("add_together")).readUInt32LE()
         ),
+        computationResult: PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("computation_result"),
+            computationOffset.toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        )[0],
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
     console.log("Queue sig is ", queueSig);
@@ -146,9 +181,10 @@ describe("SurveyX", () => {
 
     const sig = await program.methods
       .initAddTogetherCompDef()
-      .accounts({
+      .accountsPartial({
         compDefAccount: compDefPDA,
         payer: owner.publicKey,
+        config,
         mxeAccount: getMXEAccAddress(program.programId),
       })
       .signers([owner])